@@ -0,0 +1,105 @@
+//! Guards the performance of the interpreter: raw evaluation throughput, the
+//! cost of compiling definitions, and the definition chain from the
+//! alloc-attack test, which must stay cheap since words are never inlined.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use forth::Forth;
+
+// `n` arithmetic and stack words, in groups of eight that leave the stack
+// balanced
+fn arithmetic(n: usize) -> String {
+    "1 2 + 3 * dup - drop ".repeat(n / 8)
+}
+
+fn eval_throughput(c: &mut Criterion) {
+    let mut group = c.benchmark_group("eval");
+
+    let input = arithmetic(8_000);
+    group.throughput(Throughput::Elements(8_000));
+    group.bench_function("interpreted", |b| {
+        let mut f = Forth::new();
+        b.iter(|| f.eval(black_box(&input)).unwrap());
+    });
+
+    // the same work inside a compiled loop
+    let mut f = Forth::new();
+    let body = arithmetic(8);
+    f.eval(&format!(": work 1000 0 do {body} loop ;")).unwrap();
+    group.bench_function("compiled", |b| b.iter(|| f.eval("work").unwrap()));
+
+    let mut f = Forth::new();
+    f.eval(": fib dup 2 < if else dup 1 - recurse swap 2 - recurse + then ;")
+        .unwrap();
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("recursive fib 15", |b| {
+        b.iter(|| {
+            f.eval("15 fib drop").unwrap();
+        })
+    });
+    group.finish();
+}
+
+fn definition_cost(c: &mut Criterion) {
+    let mut group = c.benchmark_group("define");
+    let short = ": sq dup * ;";
+    group.bench_function("short", |b| {
+        b.iter_batched(
+            Forth::new,
+            |mut f| f.eval(black_box(short)).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+
+    let long = format!(": long {};", arithmetic(1_000));
+    group.bench_function("1000 words", |b| {
+        b.iter_batched(
+            Forth::new,
+            |mut f| f.eval(black_box(&long)).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+
+    // redefining the same name over and over keeps every old version
+    group.bench_function("100 redefinitions", |b| {
+        b.iter_batched(
+            || {
+                let mut f = Forth::new();
+                f.eval(": foo 0 ;").unwrap();
+                f
+            },
+            |mut f| {
+                for _ in 0..100 {
+                    f.eval(": foo foo 1 + ;").unwrap();
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn alloc_attack(c: &mut Criterion) {
+    let names: Vec<char> = ('a'..='z').collect();
+    let mut chain = vec![": a 0 drop ;".to_string()];
+    for pair in names.windows(2) {
+        chain.push(format!(": {} {} {} ;", pair[1], pair[0], pair[0]));
+    }
+    let source = chain.join("\n");
+
+    let mut group = c.benchmark_group("alloc attack");
+    group.bench_function("define", |b| {
+        b.iter_batched(
+            Forth::new,
+            |mut f| f.eval(black_box(&source)).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    // `m` runs 2^12 calls of `a`
+    let mut f = Forth::new();
+    f.eval(&source).unwrap();
+    group.bench_function("run m", |b| b.iter(|| f.eval("m").unwrap()));
+    group.finish();
+}
+
+criterion_group!(benches, eval_throughput, definition_cost, alloc_attack);
+criterion_main!(benches);
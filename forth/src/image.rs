@@ -0,0 +1,309 @@
+//! Saving and loading sessions.
+//!
+//! An image holds the stack, the data space and the whole dictionary,
+//! including shadowed definitions, so that loaded words behave exactly as
+//! they did when saved. The format doesn't depend on the cell width or on
+//! the order of the builtin table:
+//!
+//! ```text
+//! image   = "FRTH" version:u8 stack memory strings natives words code
+//! stack   = n:uint int*n          memory = n:uint int*n
+//! strings = n:uint str*n          natives = n:uint str*n
+//! words   = n:uint (str start:uint here:uint)*n
+//! code    = n:uint op*n
+//! op      = tag:u8 operand?
+//! str     = len:uint utf-8 bytes
+//! ```
+//!
+//! `uint` is unsigned LEB128 and `int` is zigzag encoded LEB128. Builtins are
+//! stored by name, native words by the name they were defined under, to be
+//! found again in the interpreter loading the image.
+
+use std::io::{Read, Write};
+
+use crate::{Dictionary, Error, Forth, Native, Op, Result, UnitResult, Value, Word};
+use crate::{LOOKUP, OPMAP};
+
+const MAGIC: &[u8; 4] = b"FRTH";
+const VERSION: u8 = 1;
+
+impl Forth {
+    /// Writes the stack, data space and dictionary to `out`.
+    pub fn save(&self, mut out: impl Write) -> UnitResult {
+        let mut w = Writer(MAGIC.to_vec());
+        w.0.push(VERSION);
+        w.values(&self.stack);
+        w.values(&self.memory);
+        w.uint(self.dict.strings.len());
+        self.dict.strings.iter().for_each(|s| w.str(s));
+        w.uint(self.dict.natives.len());
+        self.dict.natives.iter().for_each(|n| w.str(&n.name));
+        w.uint(self.dict.words.len());
+        for word in &self.dict.words {
+            w.str(&word.name);
+            w.uint(word.start);
+            w.uint(word.here);
+        }
+        w.uint(self.dict.code.len());
+        self.dict.code.iter().for_each(|&op| w.op(op));
+        out.write_all(&w.0).map_err(|e| Error::Io(e.kind()))
+    }
+
+    /// Replaces the stack, data space and dictionary with an image written by
+    /// `save`. Native words in the image must already be defined here under
+    /// the same name. On error nothing changes. Fails with `Error::Busy` when
+    /// called by a native word.
+    pub fn load(&mut self, mut input: impl Read) -> UnitResult {
+        self.idle()?;
+        let mut buf = vec![];
+        input
+            .read_to_end(&mut buf)
+            .map_err(|e| Error::Io(e.kind()))?;
+        let mut r = Reader(&buf);
+        if r.take(MAGIC.len())? != MAGIC || r.byte()? != VERSION {
+            return Err(Error::InvalidImage);
+        }
+        let stack = r.values()?;
+        let memory = r.values()?;
+        if stack.len() > self.limits.max_stack_depth || memory.len() > crate::DATA_SPACE {
+            return Err(Error::InvalidImage);
+        }
+        let strings = r.list(Reader::str)?;
+        let natives = r
+            .list(Reader::str)?
+            .into_iter()
+            .map(|name| self.native(&name))
+            .collect::<Result<Vec<_>>>()?;
+        let words = r.list(|r| {
+            Ok(Word {
+                name: r.str()?,
+                start: r.uint()?,
+                here: r.uint()?,
+            })
+        })?;
+        if words.len() > self.limits.max_words {
+            return Err(Error::DictionaryFull);
+        }
+        let code = r.list(Reader::op)?;
+        if !r.0.is_empty() {
+            return Err(Error::InvalidImage);
+        }
+
+        let dict = Dictionary {
+            names: words
+                .iter()
+                .enumerate()
+                .map(|(i, w)| (w.name.clone(), i))
+                .collect(),
+            code,
+            words,
+            strings,
+            natives,
+        };
+        dict.validate()?;
+        self.stack = stack;
+        self.memory = memory;
+        self.dict = dict;
+        Ok(())
+    }
+
+    // the native word currently bound to `name`
+    fn native(&self, name: &str) -> Result<Native> {
+        let &w = self.dict.names.get(name).ok_or(Error::UnknownWord)?;
+        match self.body(w) {
+            [Op::Native(f)] => Ok(self.dict.natives[*f].clone()),
+            _ => Err(Error::UnknownWord),
+        }
+    }
+}
+
+impl Dictionary {
+    // Checks that running the code can't index out of bounds: the words
+    // cover `code` in order, every jump lands inside it, strings lie within
+    // the data space and the last instruction is a `Return`, so execution
+    // never runs off the end.
+    fn validate(&self) -> UnitResult {
+        let len = self.code.len();
+        let op_ok = |op: &Op| match *op {
+            Op::Word(w) => w < self.words.len(),
+            Op::Print(s) => s < self.strings.len(),
+            Op::Native(f) => f < self.natives.len(),
+            Op::Str(addr, len) => addr
+                .checked_add(len)
+                .is_some_and(|end| end <= crate::DATA_SPACE),
+            Op::If(to)
+            | Op::Else(to)
+            | Op::While(to)
+            | Op::Repeat(to)
+            | Op::Until(to)
+            | Op::Do(to)
+            | Op::Loop(to)
+            | Op::Leave(to) => to < len,
+            _ => true,
+        };
+        let starts: Vec<_> = self.words.iter().map(|w| w.start).collect();
+        let valid = starts.first().map_or(len == 0, |&start| start == 0)
+            && starts.windows(2).all(|w| w[0] < w[1])
+            && starts.last().is_none_or(|&start| start < len)
+            && self.code.iter().all(op_ok)
+            && self.code.last().is_none_or(|op| matches!(op, Op::Return));
+        if valid {
+            Ok(())
+        } else {
+            Err(Error::InvalidImage)
+        }
+    }
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn uint(&mut self, n: usize) {
+        self.leb(n as u128);
+    }
+
+    // `Value` is i128 with the i128 feature
+    #[allow(clippy::unnecessary_cast)]
+    fn int(&mut self, v: Value) {
+        let v = v as i128;
+        self.leb(((v << 1) ^ (v >> 127)) as u128);
+    }
+
+    fn leb(&mut self, mut n: u128) {
+        while n >= 0x80 {
+            self.0.push(n as u8 | 0x80);
+            n >>= 7;
+        }
+        self.0.push(n as u8);
+    }
+
+    fn str(&mut self, s: &str) {
+        self.uint(s.len());
+        self.0.extend_from_slice(s.as_bytes());
+    }
+
+    fn values(&mut self, values: &[Value]) {
+        self.uint(values.len());
+        values.iter().for_each(|&v| self.int(v));
+    }
+
+    fn op(&mut self, op: Op) {
+        let (tag, operand) = match op {
+            Op::Call(f) => {
+                self.0.push(0);
+                return self.str(LOOKUP[f]);
+            }
+            Op::Num(v) => {
+                self.0.push(1);
+                return self.int(v);
+            }
+            Op::Word(w) => (2, Some(w)),
+            Op::Return => (3, None),
+            Op::If(to) => (4, Some(to)),
+            Op::Else(to) => (5, Some(to)),
+            Op::While(to) => (6, Some(to)),
+            Op::Repeat(to) => (7, Some(to)),
+            Op::Until(to) => (8, Some(to)),
+            Op::Do(to) => (9, Some(to)),
+            Op::Loop(to) => (10, Some(to)),
+            Op::Leave(to) => (11, Some(to)),
+            Op::I => (12, None),
+            Op::J => (13, None),
+            Op::Print(s) => (14, Some(s)),
+            Op::Native(f) => (15, Some(f)),
+            Op::ToR => (17, None),
+            Op::RFrom => (18, None),
+            Op::RFetch => (19, None),
+            Op::Str(addr, len) => {
+                self.0.push(16);
+                self.uint(addr);
+                return self.uint(len);
+            }
+        };
+        self.0.push(tag);
+        if let Some(n) = operand {
+            self.uint(n);
+        }
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if n > self.0.len() {
+            return Err(Error::InvalidImage);
+        }
+        let (head, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn leb(&mut self) -> Result<u128> {
+        let mut n = 0u128;
+        for shift in (0..128).step_by(7) {
+            let b = self.byte()?;
+            n |= u128::from(b & 0x7f) << shift;
+            if b & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        Err(Error::InvalidImage)
+    }
+
+    fn uint(&mut self) -> Result<usize> {
+        usize::try_from(self.leb()?).map_err(|_| Error::InvalidImage)
+    }
+
+    #[allow(clippy::useless_conversion)]
+    fn int(&mut self) -> Result<Value> {
+        let n = self.leb()?;
+        let v = (n >> 1) as i128 ^ -((n & 1) as i128);
+        crate::checked(v.try_into().ok())
+    }
+
+    fn str(&mut self) -> Result<String> {
+        let len = self.uint()?;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| Error::InvalidImage)
+    }
+
+    fn values(&mut self) -> Result<Vec<Value>> {
+        self.list(Reader::int)
+    }
+
+    fn list<T>(&mut self, mut item: impl FnMut(&mut Self) -> Result<T>) -> Result<Vec<T>> {
+        let n = self.uint()?;
+        (0..n).map(|_| item(self)).collect()
+    }
+
+    fn op(&mut self) -> Result<Op> {
+        Ok(match self.byte()? {
+            0 => Op::Call(*OPMAP.get(self.str()?.as_str()).ok_or(Error::UnknownWord)?),
+            1 => Op::Num(self.int()?),
+            2 => Op::Word(self.uint()?),
+            3 => Op::Return,
+            4 => Op::If(self.uint()?),
+            5 => Op::Else(self.uint()?),
+            6 => Op::While(self.uint()?),
+            7 => Op::Repeat(self.uint()?),
+            8 => Op::Until(self.uint()?),
+            9 => Op::Do(self.uint()?),
+            10 => Op::Loop(self.uint()?),
+            11 => Op::Leave(self.uint()?),
+            12 => Op::I,
+            13 => Op::J,
+            14 => Op::Print(self.uint()?),
+            15 => Op::Native(self.uint()?),
+            16 => Op::Str(self.uint()?, self.uint()?),
+            17 => Op::ToR,
+            18 => Op::RFrom,
+            19 => Op::RFetch,
+            _ => return Err(Error::InvalidImage),
+        })
+    }
+}
@@ -1,60 +1,278 @@
-use std::collections::HashMap;
+use std::any::Any;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::Arc;
+
+mod image;
 
 use once_cell::sync::Lazy;
 
+/// The cell type. Defaults to `i32`; the `i64` and `i128` features widen it
+/// (the wider one wins when both are enabled).
+///
+/// Cargo unifies features across the dependency graph, so enabling one of
+/// these changes `Value` for every crate using forth in the same build. Only
+/// the final binary should enable them; libraries must not, and should not
+/// assume a particular width either.
+#[cfg(not(any(feature = "i64", feature = "i128")))]
 pub type Value = i32;
+#[cfg(all(feature = "i64", not(feature = "i128")))]
+pub type Value = i64;
+#[cfg(feature = "i128")]
+pub type Value = i128;
 pub type Result<T> = std::result::Result<T, Error>;
 type UnitResult = Result<()>;
 
 type Fop = fn(&mut Forth) -> UnitResult;
 
-const LOOKUP: [&str; 8] = ["+", "-", "*", "/", "dup", "swap", "over", "drop"];
-static OPMAP: Lazy<HashMap<&str, Fop>> = Lazy::new(|| {
-    let mut map: HashMap<&str, Fop> = HashMap::with_capacity(LOOKUP.len());
-    LOOKUP.iter().for_each(|&op| {
-        let call = match op {
-            "+" => Forth::add,
-            "-" => Forth::sub,
-            "*" => Forth::mul,
-            "/" => Forth::div,
-            "dup" => Forth::dup,
-            "swap" => Forth::swap,
-            "over" => Forth::over,
-            "drop" => Forth::drop,
-            _ => panic!("invalid operation code"),
-        };
-        map.insert(op, call);
-    });
-    map
-});
+const LOOKUP: [&str; 49] = [
+    "+", "-", "*", "/", "mod", "/mod", "negate", "abs", "min", "max", "dup", "swap", "over",
+    "drop", "@", "!", "+!", ",", "here", "allot", ".", "emit", "cr", ".s", "words", "type",
+    "count", "rot", "-rot", "nip", "tuck", "pick", "roll", "2dup", "2drop", "2swap", "2over",
+    "depth", "=", "<", ">", "<>", "0=", "and", "or", "xor", "invert", "lshift", "rshift",
+];
+// builtins, in the same order as `LOOKUP`
+static BUILTINS: [Fop; LOOKUP.len()] = [
+    Forth::add,
+    Forth::sub,
+    Forth::mul,
+    Forth::div,
+    Forth::modulo,
+    Forth::divmod,
+    Forth::negate,
+    Forth::abs,
+    Forth::min,
+    Forth::max,
+    Forth::dup,
+    Forth::swap,
+    Forth::over,
+    Forth::drop,
+    Forth::fetch,
+    Forth::store,
+    Forth::plus_store,
+    Forth::comma,
+    Forth::here,
+    Forth::allot,
+    Forth::dot,
+    Forth::emit,
+    Forth::cr,
+    Forth::dot_s,
+    Forth::print_words,
+    Forth::type_,
+    Forth::count,
+    Forth::rot,
+    Forth::minus_rot,
+    Forth::nip,
+    Forth::tuck,
+    Forth::pick,
+    Forth::roll,
+    Forth::two_dup,
+    Forth::two_drop,
+    Forth::two_swap,
+    Forth::two_over,
+    Forth::depth,
+    Forth::eq,
+    Forth::lt,
+    Forth::gt,
+    Forth::ne,
+    Forth::zero_eq,
+    Forth::and,
+    Forth::or,
+    Forth::xor,
+    Forth::invert,
+    Forth::lshift,
+    Forth::rshift,
+];
+static OPMAP: Lazy<HashMap<&str, usize>> =
+    Lazy::new(|| LOOKUP.iter().enumerate().map(|(i, &op)| (op, i)).collect());
 
-#[derive(Debug, Clone)]
+/// Size of the data space, in cells.
+pub const DATA_SPACE: usize = 1 << 16;
+
+// Strings given to S" outside a definition live in this many buffers above
+// the data space, reused in turn, so they don't take up data space.
+const STRING_BUFFERS: usize = 2;
+
+/// Depth of the return stack, bounding both nested calls and values moved
+/// there by `>R`.
+pub const RETURN_STACK: usize = 1 << 12;
+
+// Control words, only valid inside a definition.
+const CONTROL: [&str; 18] = [
+    "if", "else", "then", "do", "loop", "i", "j", "leave", "begin", "until", "while", "repeat",
+    ";", ">r", "r>", "r@", "recurse", "[char]",
+];
+
+/// A single instruction of the compiled code. Jump targets are addresses in
+/// `Dictionary::code`.
+#[derive(Debug, Clone, Copy)]
 enum Op {
-    Call(Fop),
-    Num(i32),
-    Ops(Vec<Vec<Op>>),
-    Key((String, usize)),
+    /// Builtin, index into `BUILTINS`.
+    Call(usize),
+    Num(Value),
+    /// User defined word, index into `Dictionary::words`.
+    Word(usize),
+    Return,
+    // The jumps keep the control word they were compiled from, so that
+    // definitions can be decompiled. IF, WHILE and UNTIL jump when the top of
+    // the stack is zero, ELSE and REPEAT always do.
+    If(usize),
+    Else(usize),
+    While(usize),
+    Repeat(usize),
+    Until(usize),
+    /// Enters a loop, or jumps past it when there is nothing to iterate.
+    Do(usize),
+    /// Jumps back to the loop body while the index is below the limit.
+    Loop(usize),
+    Leave(usize),
+    I,
+    J,
+    /// Prints a `."` string, index into `Dictionary::strings`.
+    Print(usize),
+    /// Host-defined word, index into `Dictionary::natives`.
+    Native(usize),
+    /// Pushes the address and length of an `S"` string in the data space.
+    Str(usize, usize),
+    ToR,
+    RFrom,
+    RFetch,
 }
 
-impl Op {
-    fn call(&self, forth: &mut Forth, ind: usize) -> UnitResult {
-        match self {
-            Op::Call(call) => call(forth),
-            Op::Num(num) => forth.push(*num),
-            Op::Ops(ops) => {
-                for op in &ops[ind] {
-                    op.call(forth, ind)?;
-                }
-                Ok(())
-            }
-            Op::Key((k, i)) => {
-                if let Some(op) = forth.ops.get(k).cloned() {
-                    op.call(forth, *i)?;
-                    Ok(())
-                } else {
-                    Err(Error::InvalidWord)
-                }
-            }
+#[derive(Debug, Clone)]
+struct Word {
+    name: String,
+    // address of the first instruction
+    start: usize,
+    // end of the data space when the word was defined, restored by FORGET
+    here: usize,
+}
+
+/// All definitions share one flat `code` vector. A redefinition appends a
+/// new `Word` and rebinds the name, while words compiled earlier keep calling
+/// the old index.
+#[derive(Debug, Default, Clone)]
+struct Dictionary {
+    code: Vec<Op>,
+    words: Vec<Word>,
+    names: HashMap<String, usize>,
+    strings: Vec<String>,
+    natives: Vec<Native>,
+}
+
+/// A word implemented by the host application, see `Forth::define_native`.
+#[derive(Clone)]
+struct Native {
+    // the name it was defined under, to find it again when loading an image
+    name: String,
+    f: Arc<dyn Fn(&mut Forth) -> UnitResult + Send + Sync>,
+}
+
+impl fmt::Debug for Native {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Native")
+    }
+}
+
+// Pending jumps while compiling a definition, patched once the closing
+// control word is reached.
+enum Ctl {
+    If(usize),
+    Else(usize),
+    Do(usize, Vec<usize>),
+    Begin(usize),
+    While(usize, usize),
+}
+
+/// Where the output words write to, stdout by default.
+struct Output(Box<dyn Write + Send>);
+
+impl Default for Output {
+    fn default() -> Self {
+        Output(Box::new(io::stdout()))
+    }
+}
+
+impl fmt::Debug for Output {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Output")
+    }
+}
+
+/// The hook set by `Forth::set_trace`.
+struct Tracer(Box<dyn FnMut(&TraceEvent) -> bool + Send>);
+
+impl fmt::Debug for Tracer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Tracer")
+    }
+}
+
+/// An instruction about to run, as passed to the hook set by
+/// `Forth::set_trace`.
+#[derive(Debug)]
+pub struct TraceEvent<'a> {
+    /// The word the instruction belongs to.
+    pub word: &'a str,
+    /// Address of the instruction in the compiled code.
+    pub address: usize,
+    /// The instruction, spelled as in the source of `word`.
+    pub instruction: String,
+    pub stack: &'a [Value],
+    /// Nesting of the word, 1 for a word called from the input.
+    pub depth: usize,
+}
+
+impl fmt::Display for TraceEvent<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let indent = 2 * (self.depth - 1);
+        write!(
+            f,
+            "{:indent$}{} {}: {:<12} <{}>",
+            "",
+            self.word,
+            self.address,
+            self.instruction,
+            self.stack.len()
+        )?;
+        self.stack.iter().try_for_each(|v| write!(f, " {v}"))
+    }
+}
+
+/// A copy of the stack, dictionary and data space taken by
+/// `Forth::checkpoint`, along with the buffers of interpreted `S"` strings
+/// that addresses on the stack may point into.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    stack: Vec<Value>,
+    dict: Dictionary,
+    memory: Vec<Value>,
+    strings: [Vec<Value>; STRING_BUFFERS],
+    next_string: usize,
+}
+
+/// Bounds on the resources a script may use, all unlimited by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForthLimits {
+    /// Values on the data stack.
+    pub max_stack_depth: usize,
+    /// Instructions executed by compiled words during a single `eval`.
+    pub max_instructions: usize,
+    /// Entries in the dictionary, counting every definition including
+    /// shadowed ones.
+    pub max_words: usize,
+    /// Instructions compiled for a single definition.
+    pub max_definition_len: usize,
+}
+
+impl Default for ForthLimits {
+    fn default() -> Self {
+        ForthLimits {
+            max_stack_depth: usize::MAX,
+            max_instructions: usize::MAX,
+            max_words: usize::MAX,
+            max_definition_len: usize::MAX,
         }
     }
 }
@@ -62,15 +280,153 @@ impl Op {
 #[derive(Debug, Default)]
 pub struct Forth {
     pub(crate) stack: Vec<Value>,
-    ops: HashMap<String, Op>,
+    dict: Dictionary,
+    // return addresses of the words being executed, with the depth of
+    // `rvalues` when each was called
+    rstack: Vec<(usize, usize)>,
+    // values moved off the data stack by >R
+    rvalues: Vec<Value>,
+    // (index, limit) of the active DO loops, innermost last
+    loops: Vec<(Value, Value)>,
+    // data space, grown by ALLOT up to `DATA_SPACE` cells
+    memory: Vec<Value>,
+    // the buffers for interpreted S", the one at address `DATA_SPACE * (i + 1)`
+    // is `strings[i]`
+    strings: [Vec<Value>; STRING_BUFFERS],
+    // the buffer the next interpreted S" overwrites
+    next_string: usize,
+    out: Output,
+    limits: ForthLimits,
+    // instructions executed during the current `eval`
+    executed: usize,
+    // words started from the input and not finished yet, more than one when
+    // a native word calls `eval`
+    running: usize,
+    // words active when the last error occurred, outermost first
+    trace: Vec<String>,
+    // application state for native words
+    context: Option<Box<dyn Any + Send>>,
+    tracer: Option<Tracer>,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Error {
     DivisionByZero,
     StackUnderflow,
     UnknownWord,
     InvalidWord,
+    /// The result of an arithmetic word, or a number literal, doesn't fit in a cell.
+    Overflow,
+    /// A control word was interpreted outside of a definition.
+    CompileOnly,
+    /// IF/ELSE/THEN, DO/LOOP or BEGIN/UNTIL/WHILE/REPEAT don't pair up.
+    UnbalancedControl,
+    /// An address outside of the allotted data space.
+    InvalidAddress,
+    /// ALLOT or `,` beyond `DATA_SPACE` cells.
+    DataSpaceFull,
+    /// Writing to the output, or reading or writing an image, failed.
+    Io(io::ErrorKind),
+    /// Pushing beyond `ForthLimits::max_stack_depth`.
+    StackOverflow,
+    /// `ForthLimits::max_instructions` were executed without finishing.
+    InstructionLimit,
+    /// `ForthLimits::max_words` definitions already exist.
+    DictionaryFull,
+    /// A definition exceeds `ForthLimits::max_definition_len`.
+    DefinitionTooLong,
+    /// `Forth::load` was given something that isn't a valid image.
+    InvalidImage,
+    /// R> or R@ without a value moved there by >R.
+    ReturnStackUnderflow,
+    /// Calls nested, or values moved by >R, beyond `RETURN_STACK`.
+    ReturnStackOverflow,
+    /// A word returned without moving back the values it moved by >R.
+    UnbalancedReturnStack,
+    /// The trace hook stopped execution.
+    Interrupted,
+    /// A native word called `forget`, `restore` or `load`, which would
+    /// remove the code being run.
+    Busy,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::DivisionByZero => write!(f, "division by zero"),
+            Error::StackUnderflow => write!(f, "stack underflow"),
+            Error::UnknownWord => write!(f, "unknown word"),
+            Error::InvalidWord => write!(f, "invalid word"),
+            Error::Overflow => write!(f, "arithmetic overflow"),
+            Error::CompileOnly => write!(f, "control word outside of a definition"),
+            Error::UnbalancedControl => write!(f, "unbalanced control structure"),
+            Error::InvalidAddress => write!(f, "invalid address"),
+            Error::DataSpaceFull => write!(f, "data space full"),
+            Error::Io(kind) => write!(f, "i/o error: {kind}"),
+            Error::StackOverflow => write!(f, "stack overflow"),
+            Error::InstructionLimit => write!(f, "instruction limit exceeded"),
+            Error::DictionaryFull => write!(f, "dictionary full"),
+            Error::DefinitionTooLong => write!(f, "definition too long"),
+            Error::InvalidImage => write!(f, "invalid image"),
+            Error::ReturnStackUnderflow => write!(f, "return stack underflow"),
+            Error::ReturnStackOverflow => write!(f, "return stack overflow"),
+            Error::UnbalancedReturnStack => write!(f, "unbalanced return stack"),
+            Error::Interrupted => write!(f, "interrupted"),
+            Error::Busy => write!(f, "not allowed while a word is running"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// An `Error` together with where in the `eval` input it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub error: Error,
+    /// The token being interpreted or compiled.
+    pub token: String,
+    /// Byte offset of `token` in the input.
+    pub offset: usize,
+    /// 1-based line and column (in characters) of `token`.
+    pub line: usize,
+    pub column: usize,
+    /// User defined words being executed, outermost first.
+    pub trace: Vec<String>,
+}
+
+impl Diagnostic {
+    fn new(error: Error, input: &str, offset: usize, trace: Vec<String>) -> Self {
+        let token = input[offset..].split_whitespace().next().unwrap_or("");
+        let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+        Diagnostic {
+            error,
+            token: token.to_string(),
+            offset,
+            line: input[..offset].matches('\n').count() + 1,
+            column: input[line_start..offset].chars().count() + 1,
+            trace,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {}: `{}`",
+            self.error, self.line, self.column, self.token
+        )?;
+        if !self.trace.is_empty() {
+            write!(f, " (in {})", self.trace.join(" -> "))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Diagnostic {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
 }
 
 impl Forth {
@@ -78,15 +434,69 @@ impl Forth {
         Self::default()
     }
 
+    /// Creates an interpreter whose output words write to `out`.
+    pub fn with_output(out: impl Write + Send + 'static) -> Forth {
+        let mut forth = Self::default();
+        forth.set_output(out);
+        forth
+    }
+
+    /// Redirects the output words to `out`, returning the previous sink.
+    pub fn set_output(&mut self, out: impl Write + Send + 'static) -> Box<dyn Write + Send> {
+        std::mem::replace(&mut self.out.0, Box::new(out))
+    }
+
+    /// Creates an interpreter enforcing `limits`.
+    pub fn with_limits(limits: ForthLimits) -> Forth {
+        let mut forth = Self::default();
+        forth.set_limits(limits);
+        forth
+    }
+
+    pub fn set_limits(&mut self, limits: ForthLimits) {
+        self.limits = limits;
+    }
+
+    pub fn limits(&self) -> ForthLimits {
+        self.limits
+    }
+
+    /// Calls `hook` before every instruction of a compiled word runs.
+    /// Returning false stops execution with `Error::Interrupted`, which lets
+    /// a debugger step through a word and abandon it.
+    ///
+    /// ```
+    /// # use forth::Forth;
+    /// let mut f = Forth::new();
+    /// f.set_trace(|event| {
+    ///     println!("{event}");
+    ///     true
+    /// });
+    /// f.eval(": sq dup * ; 3 sq").unwrap();
+    /// ```
+    pub fn set_trace(&mut self, hook: impl FnMut(&TraceEvent) -> bool + Send + 'static) {
+        self.tracer = Some(Tracer(Box::new(hook)));
+    }
+
+    /// Removes the hook set by `set_trace`.
+    pub fn clear_trace(&mut self) {
+        self.tracer = None;
+    }
+
     pub fn stack(&self) -> &[Value] {
         &self.stack
     }
 
-    fn pop(&mut self) -> Result<Value> {
+    /// Removes the top of the stack, for use by native words.
+    pub fn pop(&mut self) -> Result<Value> {
         self.stack.pop().ok_or(Error::StackUnderflow)
     }
 
-    fn push(&mut self, val: Value) -> UnitResult {
+    /// Pushes `val`, subject to `ForthLimits::max_stack_depth`.
+    pub fn push(&mut self, val: Value) -> UnitResult {
+        if self.stack.len() >= self.limits.max_stack_depth {
+            return Err(Error::StackOverflow);
+        }
         self.stack.push(val);
         Ok(())
     }
@@ -95,17 +505,95 @@ impl Forth {
         self.stack.len()
     }
 
+    fn loop_index(&self, depth: usize) -> Result<Value> {
+        let len = self.loops.len();
+        if depth >= len {
+            return Err(Error::UnbalancedControl);
+        }
+        Ok(self.loops[len - 1 - depth].0)
+    }
+
     fn add(&mut self) -> UnitResult {
-        self.arth(|a, b| a + b)
+        self.arth(|a, b| checked(a.checked_add(b)))
     }
     fn sub(&mut self) -> UnitResult {
-        self.arth(|a, b| a - b)
+        self.arth(|a, b| checked(a.checked_sub(b)))
     }
     fn mul(&mut self) -> UnitResult {
-        self.arth(|a, b| a * b)
+        self.arth(|a, b| checked(a.checked_mul(b)))
     }
     fn div(&mut self) -> UnitResult {
-        self.arth(|a, b| a / b)
+        self.arth(|a, b| checked(a.checked_div(nonzero(b)?)))
+    }
+    fn modulo(&mut self) -> UnitResult {
+        self.arth(|a, b| checked(a.checked_rem(nonzero(b)?)))
+    }
+    fn min(&mut self) -> UnitResult {
+        self.arth(|a, b| Ok(a.min(b)))
+    }
+    fn max(&mut self) -> UnitResult {
+        self.arth(|a, b| Ok(a.max(b)))
+    }
+    fn negate(&mut self) -> UnitResult {
+        let v = self.pop()?;
+        self.push(checked(v.checked_neg())?)
+    }
+    fn abs(&mut self) -> UnitResult {
+        let v = self.pop()?;
+        self.push(checked(v.checked_abs())?)
+    }
+
+    // ( a b -- remainder quotient ), truncating like `/`
+    fn divmod(&mut self) -> UnitResult {
+        let (b, a) = (self.pop()?, self.pop()?);
+        let b = nonzero(b)?;
+        let rem = checked(a.checked_rem(b))?;
+        self.push(rem)?;
+        self.push(checked(a.checked_div(b))?)
+    }
+
+    fn eq(&mut self) -> UnitResult {
+        self.arth(|a, b| Ok(flag(a == b)))
+    }
+    fn lt(&mut self) -> UnitResult {
+        self.arth(|a, b| Ok(flag(a < b)))
+    }
+    fn gt(&mut self) -> UnitResult {
+        self.arth(|a, b| Ok(flag(a > b)))
+    }
+    fn ne(&mut self) -> UnitResult {
+        self.arth(|a, b| Ok(flag(a != b)))
+    }
+    fn zero_eq(&mut self) -> UnitResult {
+        let v = self.pop()?;
+        self.push(flag(v == 0))
+    }
+    fn and(&mut self) -> UnitResult {
+        self.arth(|a, b| Ok(a & b))
+    }
+    fn or(&mut self) -> UnitResult {
+        self.arth(|a, b| Ok(a | b))
+    }
+    fn xor(&mut self) -> UnitResult {
+        self.arth(|a, b| Ok(a ^ b))
+    }
+    fn invert(&mut self) -> UnitResult {
+        let v = self.pop()?;
+        self.push(!v)
+    }
+    // bits shifted out are lost, shifting by the cell width or more gives 0
+    fn lshift(&mut self) -> UnitResult {
+        self.arth(|a, b| Ok(shift(b).map_or(0, |n| a << n)))
+    }
+    // logical: zeros are shifted in from the left
+    fn rshift(&mut self) -> UnitResult {
+        self.arth(|a, b| {
+            Ok(match shift(b) {
+                Some(0) => a,
+                Some(n) => (a >> n) & (Value::MAX >> (n - 1)),
+                None => 0,
+            })
+        })
     }
 
     fn drop(&mut self) -> UnitResult {
@@ -138,94 +626,1084 @@ impl Forth {
         Ok(())
     }
 
-    fn arth(&mut self, f: fn(i32, i32) -> i32) -> UnitResult {
-        let (v1, v2) = (self.pop()?, self.pop()?);
-        if v1 == 0 && f(2, 2) == 1 {
-            return Err(Error::DivisionByZero);
+    // fails unless the stack holds at least `n` values
+    fn need(&self, n: usize) -> UnitResult {
+        if self.len() < n {
+            return Err(Error::StackUnderflow);
+        }
+        Ok(())
+    }
+
+    /// `( a b c -- b c a )`
+    fn rot(&mut self) -> UnitResult {
+        self.need(3)?;
+        let len = self.len();
+        self.stack[len - 3..].rotate_left(1);
+        Ok(())
+    }
+
+    /// `( a b c -- c a b )`
+    fn minus_rot(&mut self) -> UnitResult {
+        self.need(3)?;
+        let len = self.len();
+        self.stack[len - 3..].rotate_right(1);
+        Ok(())
+    }
+
+    /// `( a b -- b )`
+    fn nip(&mut self) -> UnitResult {
+        self.need(2)?;
+        let len = self.len();
+        self.stack.remove(len - 2);
+        Ok(())
+    }
+
+    /// `( a b -- b a b )`
+    fn tuck(&mut self) -> UnitResult {
+        self.need(2)?;
+        self.dup()?;
+        let len = self.len();
+        self.stack.swap(len - 3, len - 2);
+        Ok(())
+    }
+
+    // index of the `u`th value below the top, after popping `u`
+    fn below(&mut self) -> Result<usize> {
+        let u = self.pop()?;
+        usize::try_from(u)
+            .ok()
+            .filter(|&u| u < self.len())
+            .map(|u| self.len() - 1 - u)
+            .ok_or(Error::StackUnderflow)
+    }
+
+    /// `( xu ... x0 u -- xu ... x0 xu )`
+    fn pick(&mut self) -> UnitResult {
+        let at = self.below()?;
+        self.push(self.stack[at])
+    }
+
+    /// `( xu ... x0 u -- xu-1 ... x0 xu )`
+    fn roll(&mut self) -> UnitResult {
+        let at = self.below()?;
+        self.stack[at..].rotate_left(1);
+        Ok(())
+    }
+
+    /// `( a b -- a b a b )`
+    fn two_dup(&mut self) -> UnitResult {
+        self.need(2)?;
+        self.over()?;
+        self.over()
+    }
+
+    /// `( a b -- )`
+    fn two_drop(&mut self) -> UnitResult {
+        self.need(2)?;
+        self.stack.truncate(self.len() - 2);
+        Ok(())
+    }
+
+    /// `( a b c d -- c d a b )`
+    fn two_swap(&mut self) -> UnitResult {
+        self.need(4)?;
+        let len = self.len();
+        self.stack[len - 4..].rotate_left(2);
+        Ok(())
+    }
+
+    /// `( a b c d -- a b c d a b )`
+    fn two_over(&mut self) -> UnitResult {
+        self.need(4)?;
+        let len = self.len();
+        let (a, b) = (self.stack[len - 4], self.stack[len - 3]);
+        self.push(a)?;
+        self.push(b)
+    }
+
+    fn depth(&mut self) -> UnitResult {
+        self.push(self.len() as Value)
+    }
+
+    // the data space, followed by the string buffers
+    fn areas(&mut self) -> impl Iterator<Item = &mut Vec<Value>> {
+        std::iter::once(&mut self.memory).chain(&mut self.strings)
+    }
+
+    // the `len` cells from `addr`, in the data space or a string buffer
+    fn cells(&mut self, addr: Value, len: usize) -> Result<&mut [Value]> {
+        let addr = usize::try_from(addr).map_err(|_| Error::InvalidAddress)?;
+        let at = addr % DATA_SPACE;
+        self.areas()
+            .nth(addr / DATA_SPACE)
+            .and_then(|area| area.get_mut(at..at.checked_add(len)?))
+            .ok_or(Error::InvalidAddress)
+    }
+
+    fn cell(&mut self, addr: Value) -> Result<&mut Value> {
+        Ok(&mut self.cells(addr, 1)?[0])
+    }
+
+    // copies an interpreted S" string to the next string buffer
+    fn transient(&mut self, text: &str) -> Result<(Value, Value)> {
+        let i = self.next_string;
+        let buffer = &mut self.strings[i];
+        buffer.clear();
+        buffer.extend(text.chars().map(|c| c as Value));
+        if buffer.len() > DATA_SPACE {
+            buffer.clear();
+            return Err(Error::DataSpaceFull);
+        }
+        self.next_string = (i + 1) % STRING_BUFFERS;
+        Ok(((DATA_SPACE * (i + 1)) as Value, buffer.len() as Value))
+    }
+
+    // grows (or shrinks, for a negative count) the data space by `n` cells
+    fn reserve(&mut self, n: Value) -> UnitResult {
+        let count = usize::try_from(n.unsigned_abs()).unwrap_or(usize::MAX);
+        let len = if n < 0 {
+            self.memory
+                .len()
+                .checked_sub(count)
+                .ok_or(Error::InvalidAddress)?
+        } else {
+            self.memory.len().saturating_add(count)
+        };
+        if len > DATA_SPACE {
+            return Err(Error::DataSpaceFull);
         }
-        self.push(f(v2, v1))
+        self.memory.resize(len, 0);
+        Ok(())
+    }
+
+    fn here(&mut self) -> UnitResult {
+        self.push(self.memory.len() as Value)
+    }
+
+    fn allot(&mut self) -> UnitResult {
+        let n = self.pop()?;
+        self.reserve(n)
+    }
+
+    fn comma(&mut self) -> UnitResult {
+        let v = self.pop()?;
+        self.reserve(1)?;
+        *self.memory.last_mut().unwrap() = v;
+        Ok(())
+    }
+
+    fn fetch(&mut self) -> UnitResult {
+        let addr = self.pop()?;
+        let v = *self.cell(addr)?;
+        self.push(v)
+    }
+
+    fn store(&mut self) -> UnitResult {
+        let (addr, v) = (self.pop()?, self.pop()?);
+        *self.cell(addr)? = v;
+        Ok(())
+    }
+
+    fn plus_store(&mut self) -> UnitResult {
+        let (addr, n) = (self.pop()?, self.pop()?);
+        let cell = self.cell(addr)?;
+        *cell = checked(cell.checked_add(n))?;
+        Ok(())
+    }
+
+    fn write(&mut self, args: fmt::Arguments) -> UnitResult {
+        self.out.0.write_fmt(args).map_err(|e| Error::Io(e.kind()))
+    }
+
+    fn dot(&mut self) -> UnitResult {
+        let v = self.pop()?;
+        self.write(format_args!("{v} "))
+    }
+
+    fn emit(&mut self) -> UnitResult {
+        let c = char_of(self.pop()?);
+        self.write(format_args!("{c}"))
+    }
+
+    /// `( addr len -- )` prints the characters stored in `len` cells.
+    fn type_(&mut self) -> UnitResult {
+        let (len, addr) = (self.pop()?, self.pop()?);
+        let len = usize::try_from(len).map_err(|_| Error::InvalidAddress)?;
+        let text: String = self.cells(addr, len)?.iter().map(|&c| char_of(c)).collect();
+        self.write(format_args!("{text}"))
+    }
+
+    /// `( addr -- addr+1 len )` turns a counted string, whose first cell
+    /// holds the length, into an address and length.
+    fn count(&mut self) -> UnitResult {
+        let addr = self.pop()?;
+        let len = *self.cell(addr)?;
+        self.push(addr + 1)?;
+        self.push(len)
+    }
+
+    fn cr(&mut self) -> UnitResult {
+        self.write(format_args!("\n"))
+    }
+
+    // prints the depth and contents of the stack without changing it, e.g. `<2> 1 2 `
+    fn dot_s(&mut self) -> UnitResult {
+        let mut s = format!("<{}> ", self.len());
+        for v in &self.stack {
+            s += &format!("{v} ");
+        }
+        self.write(format_args!("{s}"))
+    }
+
+    fn print_words(&mut self) -> UnitResult {
+        let words = self.words().join(" ");
+        self.write(format_args!("{words}\n"))
+    }
+
+    fn arth(&mut self, f: fn(Value, Value) -> Result<Value>) -> UnitResult {
+        let (v1, v2) = (self.pop()?, self.pop()?);
+        self.push(f(v2, v1)?)
     }
 
     pub fn eval(&mut self, input: &str) -> UnitResult {
-        let mut iter = input.split_whitespace();
+        self.eval_diagnostic(input).map_err(|d| d.error)
+    }
+
+    /// Like `eval`, but leaves the stack, dictionary and data space untouched
+    /// if it fails. Output that was already written is not taken back.
+    pub fn eval_atomic(&mut self, input: &str) -> UnitResult {
+        self.eval_atomic_diagnostic(input).map_err(|d| d.error)
+    }
+
+    /// Like `eval_atomic`, but reports where the error happened.
+    pub fn eval_atomic_diagnostic(&mut self, input: &str) -> std::result::Result<(), Diagnostic> {
+        let checkpoint = self.checkpoint();
+        let res = self.eval_diagnostic(input);
+        if res.is_err() {
+            self.rollback(checkpoint);
+        }
+        res
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            stack: self.stack.clone(),
+            dict: self.dict.clone(),
+            memory: self.memory.clone(),
+            strings: self.strings.clone(),
+            next_string: self.next_string,
+        }
+    }
+
+    /// Returns to the state saved by `checkpoint`. Fails with `Error::Busy`
+    /// when called by a native word.
+    pub fn restore(&mut self, checkpoint: &Checkpoint) -> UnitResult {
+        self.idle()?;
+        self.rollback(checkpoint.clone());
+        Ok(())
+    }
+
+    fn rollback(&mut self, checkpoint: Checkpoint) {
+        self.stack = checkpoint.stack;
+        self.dict = checkpoint.dict;
+        self.memory = checkpoint.memory;
+        self.strings = checkpoint.strings;
+        self.next_string = checkpoint.next_string;
+    }
+
+    /// Defines `name` as a word running `f`, which works on the stack through
+    /// `pop` and `push` and reaches application state through `context`.
+    /// Native words are redefined, shadowed and forgotten like any other
+    /// word; definitions compiled earlier keep the old meaning.
+    ///
+    /// ```
+    /// # use forth::Forth;
+    /// let mut f = Forth::new();
+    /// f.set_context(vec![10, 20, 30]);
+    /// f.define_native("lookup", |f| {
+    ///     let key = f.pop()? as usize;
+    ///     let table = f.context::<Vec<i32>>().unwrap();
+    ///     let value = table.get(key).copied().unwrap_or(-1);
+    ///     f.push(value as _)
+    /// })
+    /// .unwrap();
+    /// f.eval("1 lookup 7 lookup").unwrap();
+    /// assert_eq!(f.stack(), [20, -1]);
+    /// ```
+    pub fn define_native(
+        &mut self,
+        name: &str,
+        f: impl Fn(&mut Forth) -> UnitResult + Send + Sync + 'static,
+    ) -> UnitResult {
+        let name = self.check_name(name)?;
+        let start = self.dict.code.len();
+        let native = Op::Native(self.dict.natives.len());
+        self.dict.natives.push(Native {
+            name: name.clone(),
+            f: Arc::new(f),
+        });
+        self.dict.code.extend([native, Op::Return]);
+        self.bind(name, start, self.memory.len());
+        Ok(())
+    }
+
+    /// Sets the application state available to native words, returning the
+    /// previous one.
+    pub fn set_context(&mut self, context: impl Any + Send) -> Option<Box<dyn Any + Send>> {
+        self.context.replace(Box::new(context))
+    }
+
+    /// The application state set by `set_context`, if it is a `T`.
+    pub fn context<T: Any>(&mut self) -> Option<&mut T> {
+        self.context.as_mut()?.downcast_mut()
+    }
+
+    /// Names of the words defined so far, most recent first. Shadowed
+    /// definitions are not listed.
+    pub fn words(&self) -> Vec<&str> {
+        self.dict
+            .words
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(i, w)| self.dict.names[&w.name] == *i)
+            .map(|(_, w)| w.name.as_str())
+            .collect()
+    }
+
+    /// The definition of the user defined word `name` as source text, e.g.
+    /// `: double 2 * ;`.
+    ///
+    /// Words compiled before a redefinition keep using the old meaning. If
+    /// `name` uses words that have been redefined, the versions it refers to
+    /// are listed first, one per line and oldest first, so that evaluating the
+    /// text reproduces what `name` does. Native words have no source and
+    /// are left out.
+    pub fn see(&self, name: &str) -> Option<String> {
+        let w = *self.dict.names.get(&name.to_ascii_lowercase())?;
+        if self.is_native(w) {
+            return None;
+        }
+        let mut redefined = BTreeSet::new();
+        self.redefined(w, &mut redefined);
+        let src: Vec<_> = redefined
+            .into_iter()
+            .chain([w])
+            .map(|w| self.decompile(w))
+            .collect();
+        Some(src.join("\n"))
+    }
+
+    /// Removes the word `name` and everything defined after it, along with
+    /// the data space allotted since. Shadowed definitions become visible
+    /// again. Fails with `Error::Busy` when called by a native word.
+    pub fn forget(&mut self, name: &str) -> UnitResult {
+        self.idle()?;
+        let w = *self
+            .dict
+            .names
+            .get(&name.to_ascii_lowercase())
+            .ok_or(Error::UnknownWord)?;
+        let Word { start, here, .. } = self.dict.words[w];
+        self.dict.words.truncate(w);
+        self.dict.code.truncate(start);
+        let (mut strings, mut natives) = (0, 0);
+        for op in &self.dict.code {
+            match *op {
+                Op::Print(text) => strings = text + 1,
+                Op::Native(f) => natives = f + 1,
+                _ => {}
+            }
+        }
+        self.dict.strings.truncate(strings);
+        self.dict.natives.truncate(natives);
+        self.memory.truncate(here);
+        self.dict.names = (self.dict.words.iter().enumerate())
+            .map(|(i, w)| (w.name.clone(), i))
+            .collect();
+        Ok(())
+    }
+
+    // the code of word `w`, without the final `Op::Return`
+    fn body(&self, w: usize) -> &[Op] {
+        let start = self.dict.words[w].start;
+        let code = &self.dict.code[start..];
+        &code[..code.iter().position(|op| matches!(op, Op::Return)).unwrap()]
+    }
+
+    // definitions used by `w` whose name has more than one definition,
+    // directly or through other such definitions
+    fn redefined(&self, w: usize, found: &mut BTreeSet<usize>) {
+        for op in self.body(w) {
+            if let Op::Word(used) = *op {
+                if used == w {
+                    continue;
+                }
+                let name = &self.dict.words[used].name;
+                let versions = self.dict.words.iter().filter(|w| &w.name == name);
+                if versions.count() > 1 && !self.is_native(used) && found.insert(used) {
+                    self.redefined(used, found);
+                }
+            }
+        }
+    }
+
+    fn is_native(&self, w: usize) -> bool {
+        matches!(self.body(w), [Op::Native(_)])
+    }
+
+    fn decompile(&self, w: usize) -> String {
+        let code = &self.dict.code;
+        let start = self.dict.words[w].start;
+        let end = start + self.body(w).len();
+        // BEGIN and THEN don't compile to anything, put them back in front
+        // of the instruction their jumps point at
+        let mut begins = HashMap::new();
+        for op in &code[start..end] {
+            if let Op::Until(to) | Op::Repeat(to) = op {
+                *begins.entry(*to).or_insert(0) += 1;
+            }
+        }
+        let mut thens = vec![];
+
+        let mut src = vec![":".to_string(), self.dict.words[w].name.clone()];
+        for (at, &op) in code[..=end].iter().enumerate().skip(start) {
+            while thens.last() == Some(&at) {
+                thens.pop();
+                src.push("then".to_string());
+            }
+            for _ in 0..begins.get(&at).copied().unwrap_or(0) {
+                src.push("begin".to_string());
+            }
+            match op {
+                Op::If(to) => thens.push(to),
+                Op::Else(to) => {
+                    thens.pop();
+                    thens.push(to);
+                }
+                _ => {}
+            }
+            src.push(self.instruction(w, op));
+        }
+        src.join(" ")
+    }
+
+    // the source text `op` in word `w` was compiled from
+    fn instruction(&self, w: usize, op: Op) -> String {
+        match op {
+            Op::Call(f) => LOOKUP[f].to_string(),
+            Op::Num(num) => num.to_string(),
+            Op::Word(used) if used == w => "recurse".to_string(),
+            Op::Word(used) => self.dict.words[used].name.clone(),
+            Op::Return => ";".to_string(),
+            Op::If(_) => "if".to_string(),
+            Op::Else(_) => "else".to_string(),
+            Op::While(_) => "while".to_string(),
+            Op::Repeat(_) => "repeat".to_string(),
+            Op::Until(_) => "until".to_string(),
+            Op::Do(_) => "do".to_string(),
+            Op::Loop(_) => "loop".to_string(),
+            Op::Leave(_) => "leave".to_string(),
+            Op::I => "i".to_string(),
+            Op::ToR => ">r".to_string(),
+            Op::RFrom => "r>".to_string(),
+            Op::RFetch => "r@".to_string(),
+            Op::J => "j".to_string(),
+            Op::Print(text) => format!(".\" {}\"", self.dict.strings[text]),
+            Op::Native(f) => self.dict.natives[f].name.clone(),
+            Op::Str(addr, len) => {
+                // compiled strings live in the data space, unless a negative
+                // ALLOT has released it since
+                let text: String = (addr.checked_add(len))
+                    .and_then(|end| self.memory.get(addr..end))
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|&c| char_of(c))
+                    .collect();
+                format!("s\" {text}\"")
+            }
+        }
+    }
+
+    /// Whether `input` stops inside a definition, string or `(` comment,
+    /// meaning an interactive caller should read more lines before evaluating
+    /// it.
+    pub fn is_incomplete(input: &str) -> bool {
+        let mut iter = Tokens::new(input);
+        let mut open = false;
+        while let Some(s) = iter.next() {
+            match s.to_ascii_lowercase().as_str() {
+                ":" => open = true,
+                ";" => open = false,
+                ".\"" | "s\"" if iter.until('"').is_none() => return true,
+                // their argument is a name or character, even `:` or `;`
+                "char" | "[char]" | "see" | "forget" | "variable" | "constant" | "create" => {
+                    iter.next();
+                }
+                _ => {}
+            }
+        }
+        open || iter.open_comment
+    }
+
+    /// Like `eval`, but reports where the error happened.
+    pub fn eval_diagnostic(&mut self, input: &str) -> std::result::Result<(), Diagnostic> {
+        // a native word calling `eval` counts towards the outer limit
+        if self.running == 0 {
+            self.executed = 0;
+            self.trace.clear();
+        }
+        let mut iter = Tokens::new(input);
+        let res = self.interpret(&mut iter);
+        let flushed = self.out.0.flush().map_err(|e| Error::Io(e.kind()));
+        res.and(flushed).map_err(|error| {
+            let trace = std::mem::take(&mut self.trace);
+            Diagnostic::new(error, input, iter.last, trace)
+        })
+    }
+
+    fn interpret(&mut self, iter: &mut Tokens) -> UnitResult {
         while let Some(s) = iter.next() {
             let s = s.to_ascii_lowercase();
             let s = s.as_str();
-            if let Some(op) = self.ops.get(s).cloned() {
-                match &op {
-                    Op::Ops(ops) => {
-                        op.call(self, ops.len() - 1)?;
-                    }
-                    _ => op.call(self, 0)?,
-                }
+            if let Some(&w) = self.dict.names.get(s) {
+                self.exec(w)?;
                 continue;
             }
-            // ops can be rewrite, must be after self.ops
-            if let Some(f) = OPMAP.get(s) {
-                f(self)?;
+            // ops can be rewrite, must be after self.dict
+            if let Some(&f) = OPMAP.get(s) {
+                BUILTINS[f](self)?;
                 continue;
             }
-            if let Ok(num) = str::parse::<i32>(s) {
-                self.push(num)?;
+            if let Some(num) = number(s) {
+                self.push(num?)?;
                 continue;
             }
-            if s == ":" {
-                let (w, op) = self.define(&mut iter)?;
-                self.ops.insert(w, op);
-            } else {
-                return Err(Error::UnknownWord);
+            match s {
+                ":" => self.define(iter)?,
+                ".\"" => {
+                    let text = iter.until('"').ok_or(Error::InvalidWord)?;
+                    self.write(format_args!("{text}"))?;
+                }
+                "variable" | "constant" | "create" => self.create(s, iter)?,
+                "s\"" => {
+                    let text = iter.until('"').ok_or(Error::InvalidWord)?;
+                    let (addr, len) = self.transient(text)?;
+                    self.push(addr)?;
+                    self.push(len)?;
+                }
+                "char" => {
+                    let c = iter.next().ok_or(Error::InvalidWord)?;
+                    self.push(first_char(c))?;
+                }
+                "see" => {
+                    let name = iter.next().ok_or(Error::InvalidWord)?;
+                    let lower = name.to_ascii_lowercase();
+                    match self.see(name) {
+                        Some(src) => self.write(format_args!("{src}\n"))?,
+                        // builtins and native words
+                        None if OPMAP.contains_key(lower.as_str())
+                            || self.dict.names.contains_key(&lower) =>
+                        {
+                            self.write(format_args!("{name} is a builtin\n"))?
+                        }
+                        None => return Err(Error::UnknownWord),
+                    }
+                }
+                "forget" => self.forget(iter.next().ok_or(Error::InvalidWord)?)?,
+                s if CONTROL.contains(&s) => return Err(Error::CompileOnly),
+                _ => return Err(Error::UnknownWord),
             }
         }
         Ok(())
     }
 
-    fn define<'a>(&self, iter: &mut impl Iterator<Item = &'a str>) -> Result<(String, Op)> {
-        let w = iter.next().ok_or(Error::InvalidWord)?;
-        if str::parse::<i32>(w).is_ok() {
+    /// Runs the word at index `w` until it returns, unwinding the return and
+    /// loop stacks if it fails.
+    fn exec(&mut self, w: usize) -> UnitResult {
+        let (base, loops, rvalues) = (self.rstack.len(), self.loops.len(), self.rvalues.len());
+        self.running += 1;
+        let mut res = self.run(self.dict.words[w].start, base);
+        self.running -= 1;
+        if res.is_ok() && self.rvalues.len() != rvalues {
+            self.trace = vec![self.dict.words[w].name.clone()];
+            res = Err(Error::UnbalancedReturnStack);
+        }
+        if res.is_err() {
+            self.rstack.truncate(base);
+            self.loops.truncate(loops);
+            self.rvalues.truncate(rvalues);
+        }
+        res
+    }
+
+    // fails if words are running, which code must not be removed under
+    pub(crate) fn idle(&self) -> UnitResult {
+        if self.running > 0 {
+            return Err(Error::Busy);
+        }
+        Ok(())
+    }
+
+    fn run(&mut self, mut pc: usize, base: usize) -> UnitResult {
+        loop {
+            let at = pc;
+            match self.step(&mut pc, base) {
+                Ok(true) => {}
+                Ok(false) => return Ok(()),
+                Err(e) => {
+                    if self.trace.is_empty() {
+                        self.trace = self.backtrace(at, base);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// Executes the instruction at `pc`, returning false once the outermost
+    /// word has returned.
+    fn step(&mut self, pc: &mut usize, base: usize) -> Result<bool> {
+        if self.executed >= self.limits.max_instructions {
+            return Err(Error::InstructionLimit);
+        }
+        self.executed += 1;
+        if self.tracer.is_some() {
+            self.trace_hook(*pc, base)?;
+        }
+        let op = self.dict.code[*pc];
+        *pc += 1;
+        match op {
+            Op::Call(f) => BUILTINS[f](self)?,
+            Op::Num(num) => self.push(num)?,
+            Op::Word(w) => {
+                if self.rstack.len() >= RETURN_STACK {
+                    return Err(Error::ReturnStackOverflow);
+                }
+                self.rstack.push((*pc, self.rvalues.len()));
+                *pc = self.dict.words[w].start;
+            }
+            Op::Return => {
+                if self.rstack.len() == base {
+                    return Ok(false);
+                }
+                let (ret, rvalues) = *self.rstack.last().unwrap();
+                if self.rvalues.len() != rvalues {
+                    return Err(Error::UnbalancedReturnStack);
+                }
+                self.rstack.pop();
+                *pc = ret;
+            }
+            Op::Else(to) | Op::Repeat(to) => *pc = to,
+            Op::If(to) | Op::While(to) | Op::Until(to) => {
+                if self.pop()? == 0 {
+                    *pc = to;
+                }
+            }
+            Op::Do(end) => {
+                let (start, limit) = (self.pop()?, self.pop()?);
+                if start < limit {
+                    self.loops.push((start, limit));
+                } else {
+                    *pc = end;
+                }
+            }
+            Op::Loop(body) => {
+                let (index, limit) = self.loops.last_mut().ok_or(Error::UnbalancedControl)?;
+                *index += 1;
+                if *index < *limit {
+                    *pc = body;
+                } else {
+                    self.loops.pop();
+                }
+            }
+            Op::Leave(end) => {
+                self.loops.pop();
+                *pc = end;
+            }
+            Op::I => self.push(self.loop_index(0)?)?,
+            Op::J => self.push(self.loop_index(1)?)?,
+            Op::Print(text) => {
+                let text = self.dict.strings[text].as_bytes();
+                self.out
+                    .0
+                    .write_all(text)
+                    .map_err(|e| Error::Io(e.kind()))?
+            }
+            Op::Native(f) => {
+                let f = Arc::clone(&self.dict.natives[f].f);
+                f(self)?
+            }
+            Op::Str(addr, len) => {
+                self.push(addr as Value)?;
+                self.push(len as Value)?
+            }
+            Op::ToR => {
+                if self.rvalues.len() >= RETURN_STACK {
+                    return Err(Error::ReturnStackOverflow);
+                }
+                let v = self.pop()?;
+                self.rvalues.push(v);
+            }
+            Op::RFrom => {
+                let v = self.rvalues.pop().ok_or(Error::ReturnStackUnderflow)?;
+                self.push(v)?
+            }
+            Op::RFetch => {
+                let v = *self.rvalues.last().ok_or(Error::ReturnStackUnderflow)?;
+                self.push(v)?
+            }
+        }
+        Ok(true)
+    }
+
+    // reports the instruction at `pc` to the trace hook
+    fn trace_hook(&mut self, pc: usize, base: usize) -> UnitResult {
+        let Some(Tracer(mut hook)) = self.tracer.take() else {
+            return Ok(());
+        };
+        let w = self.word_at(pc);
+        let event = TraceEvent {
+            word: &self.dict.words[w].name,
+            address: pc,
+            instruction: self.instruction(w, self.dict.code[pc]),
+            stack: &self.stack,
+            depth: self.rstack.len() - base + 1,
+        };
+        let go = hook(&event);
+        self.tracer = Some(Tracer(hook));
+        if go {
+            Ok(())
+        } else {
+            Err(Error::Interrupted)
+        }
+    }
+
+    // index of the word whose code contains `addr`
+    fn word_at(&self, addr: usize) -> usize {
+        self.dict.words.partition_point(|w| w.start <= addr) - 1
+    }
+
+    // names of the words executing at `at` and above it on the return stack
+    fn backtrace(&self, at: usize, base: usize) -> Vec<String> {
+        self.rstack[base..]
+            .iter()
+            .map(|&(ret, _)| ret - 1)
+            .chain([at])
+            .map(|addr| self.dict.words[self.word_at(addr)].name.clone())
+            .collect()
+    }
+
+    // reads the name of a new definition
+    fn name<'a>(&self, iter: &mut Tokens<'a>) -> Result<String> {
+        self.check_name(iter.next().ok_or(Error::InvalidWord)?)
+    }
+
+    fn check_name(&self, name: &str) -> Result<String> {
+        let w = name.to_ascii_lowercase();
+        if w.is_empty() || w.contains(char::is_whitespace) || number(&w).is_some() {
             return Err(Error::InvalidWord);
         }
-        let mut ops = vec![];
+        if self.dict.words.len() >= self.limits.max_words {
+            return Err(Error::DictionaryFull);
+        }
+        Ok(w)
+    }
+
+    // makes `name` refer to the code starting at `start`
+    fn bind(&mut self, name: String, start: usize, here: usize) {
+        self.dict.names.insert(name.clone(), self.dict.words.len());
+        self.dict.words.push(Word { name, start, here });
+    }
+
+    /// VARIABLE, CONSTANT and CREATE define a word pushing a single value: the
+    /// address of a new cell, the value on top of the stack, or the current
+    /// end of the data space respectively.
+    fn create<'a>(&mut self, kind: &str, iter: &mut Tokens<'a>) -> UnitResult {
+        let w = self.name(iter)?;
+        let here = self.memory.len();
+        let value = match kind {
+            "constant" => self.pop()?,
+            _ => here as Value,
+        };
+        if kind == "variable" {
+            self.reserve(1)?;
+        }
+        let start = self.dict.code.len();
+        self.dict.code.extend([Op::Num(value), Op::Return]);
+        self.bind(w, start, here);
+        Ok(())
+    }
 
-        for s in iter.by_ref() {
+    fn define<'a>(&mut self, iter: &mut Tokens<'a>) -> UnitResult {
+        let w = self.name(iter)?;
+        let (start, strings) = (self.dict.code.len(), self.dict.strings.len());
+        let here = self.memory.len();
+        match self.compile(iter) {
+            Ok(()) => {
+                self.bind(w, start, here);
+                Ok(())
+            }
+            Err(e) => {
+                self.dict.code.truncate(start);
+                self.dict.strings.truncate(strings);
+                self.memory.truncate(here);
+                Err(e)
+            }
+        }
+    }
+
+    /// Compiles the body of a definition up to and including `;`, appending it
+    /// to the end of `code`.
+    fn compile<'a>(&mut self, iter: &mut Tokens<'a>) -> UnitResult {
+        let code = &mut self.dict.code;
+        let start = code.len();
+        let mut ctl = vec![];
+
+        while let Some(s) = iter.next() {
             let s = s.to_ascii_lowercase();
-            let s = s.as_str();
-            let mut vd = vec![];
-            match s {
-                // Assume the ";" is the last
+            let here = code.len();
+            if s != ";" && here - start >= self.limits.max_definition_len {
+                return Err(Error::DefinitionTooLong);
+            }
+            // user definitions shadow builtins and control words alike
+            if let Some(&w) = self.dict.names.get(&s) {
+                code.push(Op::Word(w));
+                continue;
+            }
+            let loops = ctl.iter().filter(|c| matches!(c, Ctl::Do(..))).count();
+            match s.as_str() {
                 ";" => {
-                    if let Some(Op::Ops(v)) = self.ops.get(w) {
-                        vd.extend(v.clone());
+                    if !ctl.is_empty() {
+                        return Err(Error::UnbalancedControl);
+                    }
+                    code.push(Op::Return);
+                    return Ok(());
+                }
+                "if" => {
+                    code.push(Op::If(0));
+                    ctl.push(Ctl::If(here));
+                }
+                "else" => match ctl.pop() {
+                    Some(Ctl::If(at)) => {
+                        code.push(Op::Else(0));
+                        code[at] = Op::If(here + 1);
+                        ctl.push(Ctl::Else(here));
+                    }
+                    _ => return Err(Error::UnbalancedControl),
+                },
+                "then" => match ctl.pop() {
+                    Some(Ctl::If(at)) => code[at] = Op::If(here),
+                    Some(Ctl::Else(at)) => code[at] = Op::Else(here),
+                    _ => return Err(Error::UnbalancedControl),
+                },
+                "do" => {
+                    code.push(Op::Do(0));
+                    ctl.push(Ctl::Do(here, vec![]));
+                }
+                "loop" => match ctl.pop() {
+                    Some(Ctl::Do(at, leaves)) => {
+                        code.push(Op::Loop(at + 1));
+                        code[at] = Op::Do(here + 1);
+                        for at in leaves {
+                            code[at] = Op::Leave(here + 1);
+                        }
+                    }
+                    _ => return Err(Error::UnbalancedControl),
+                },
+                "begin" => ctl.push(Ctl::Begin(here)),
+                "until" => match ctl.pop() {
+                    Some(Ctl::Begin(at)) => code.push(Op::Until(at)),
+                    _ => return Err(Error::UnbalancedControl),
+                },
+                "while" => match ctl.pop() {
+                    Some(Ctl::Begin(at)) => {
+                        code.push(Op::While(0));
+                        ctl.push(Ctl::While(at, here));
+                    }
+                    _ => return Err(Error::UnbalancedControl),
+                },
+                "repeat" => match ctl.pop() {
+                    Some(Ctl::While(begin, at)) => {
+                        code.push(Op::Repeat(begin));
+                        code[at] = Op::While(here + 1);
+                    }
+                    _ => return Err(Error::UnbalancedControl),
+                },
+                ".\"" => {
+                    let text = iter.until('"').ok_or(Error::InvalidWord)?;
+                    code.push(Op::Print(self.dict.strings.len()));
+                    self.dict.strings.push(text.to_string());
+                }
+                "s\"" => {
+                    let text = iter.until('"').ok_or(Error::InvalidWord)?;
+                    let (addr, len) = place(&mut self.memory, text)?;
+                    code.push(Op::Str(addr, len));
+                }
+                "[char]" => {
+                    let c = iter.next().ok_or(Error::InvalidWord)?;
+                    code.push(Op::Num(first_char(c)));
+                }
+                ">r" => code.push(Op::ToR),
+                "r>" => code.push(Op::RFrom),
+                "r@" => code.push(Op::RFetch),
+                // the word isn't bound before `;`, but will get the next index
+                "recurse" => code.push(Op::Word(self.dict.words.len())),
+                "i" | "leave" if loops < 1 => return Err(Error::UnbalancedControl),
+                "j" if loops < 2 => return Err(Error::UnbalancedControl),
+                "i" => code.push(Op::I),
+                "j" => code.push(Op::J),
+                "leave" => {
+                    code.push(Op::Leave(0));
+                    if let Some(Ctl::Do(_, leaves)) =
+                        ctl.iter_mut().rev().find(|c| matches!(c, Ctl::Do(..)))
+                    {
+                        leaves.push(here);
                     }
-                    vd.push(ops);
-                    return Ok((w.to_ascii_lowercase(), Op::Ops(vd)));
                 }
-                _ => {
-                    if let Some(f) = OPMAP.get(s) {
-                        ops.push(Op::Call(*f));
+                s => {
+                    if let Some(&f) = OPMAP.get(s) {
+                        code.push(Op::Call(f));
                         continue;
                     }
-                    if let Ok(num) = str::parse::<i32>(s) {
-                        ops.push(Op::Num(num));
+                    if let Some(num) = number(s) {
+                        code.push(Op::Num(num?));
                         continue;
                     }
-                    if let Some(Op::Ops(v)) = self.ops.get(s) {
-                        if w == s {
-                            ops.extend(v.last().unwrap().clone());
+                    return Err(Error::InvalidWord);
+                }
+            }
+        }
+        Err(Error::InvalidWord)
+    }
+}
 
-                            // ops.push(op.clone());
-                        } else {
-                            ops.push(Op::Key((s.to_string(), v.len() - 1)));
-                        }
-                    } else {
-                        return Err(Error::InvalidWord);
+fn checked(v: Option<Value>) -> Result<Value> {
+    v.ok_or(Error::Overflow)
+}
+
+/// The standard truth values: all bits set for true, 0 for false.
+fn flag(b: bool) -> Value {
+    if b {
+        -1
+    } else {
+        0
+    }
+}
+
+// a shift count, `None` if it is negative or not below the cell width
+fn shift(n: Value) -> Option<u32> {
+    u32::try_from(n).ok().filter(|&n| n < Value::BITS)
+}
+
+fn nonzero(v: Value) -> Result<Value> {
+    if v == 0 {
+        return Err(Error::DivisionByZero);
+    }
+    Ok(v)
+}
+
+/// Whitespace separated tokens, with access to the raw input for words such
+/// as `."` that parse their own text. `( ... )` and `\ ...` comments are
+/// skipped. Tokens keep their case, callers lowercase word names.
+struct Tokens<'a> {
+    input: &'a str,
+    pos: usize,
+    // offset of the last token returned
+    last: usize,
+    // a `(` comment ran to the end of the input
+    open_comment: bool,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(input: &'a str) -> Self {
+        Tokens {
+            input,
+            pos: 0,
+            last: 0,
+            open_comment: false,
+        }
+    }
+
+    /// Skips the single space delimiting the previous token, then reads up to
+    /// the next `delim`, consuming it as well.
+    fn until(&mut self, delim: char) -> Option<&'a str> {
+        let rest = &self.input[self.pos..];
+        let rest = rest
+            .strip_prefix(|c: char| c.is_whitespace())
+            .unwrap_or(rest);
+        let start = self.input.len() - rest.len();
+        let len = rest.find(delim)?;
+        self.pos = start + len + delim.len_utf8();
+        Some(&rest[..len])
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        loop {
+            let rest = &self.input[self.pos..];
+            let start = self.input.len() - rest.trim_start().len();
+            let rest = &self.input[start..];
+            let len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            if len == 0 {
+                return None;
+            }
+            self.pos = start + len;
+            match &rest[..len] {
+                "(" => {
+                    if self.until(')').is_none() {
+                        self.open_comment = true;
+                        self.pos = self.input.len();
                     }
                 }
+                "\\" => {
+                    let line = self.input[self.pos..].find('\n');
+                    self.pos = line.map_or(self.input.len(), |n| self.pos + n);
+                }
+                token => {
+                    self.last = start;
+                    return Some(token);
+                }
             }
         }
-        Err(Error::InvalidWord)
     }
 }
 
+fn char_of(v: Value) -> char {
+    u32::try_from(v)
+        .ok()
+        .and_then(char::from_u32)
+        .unwrap_or(char::REPLACEMENT_CHARACTER)
+}
+
+fn first_char(s: &str) -> Value {
+    s.chars().next().map_or(0, |c| c as Value)
+}
+
+/// Copies `text` into the data space, one character per cell, returning its
+/// address and length.
+fn place(memory: &mut Vec<Value>, text: &str) -> Result<(usize, usize)> {
+    let addr = memory.len();
+    memory.extend(text.chars().map(|c| c as Value));
+    if memory.len() > DATA_SPACE {
+        memory.truncate(addr);
+        return Err(Error::DataSpaceFull);
+    }
+    Ok((addr, memory.len() - addr))
+}
+
+/// Parses a number literal, `None` if `s` isn't one at all.
+fn number(s: &str) -> Option<Result<Value>> {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some(s.parse().map_err(|_| Error::Overflow))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -269,4 +1747,25 @@ mod tests {
 
         // assert_eq!(vec![11], f.stack());
     }
+
+    #[test]
+    fn definitions_are_compiled_flat() {
+        let mut f = Forth::new();
+        f.eval(": a 0 drop ;").unwrap();
+        f.eval(": b a a ;").unwrap();
+        f.eval(": c b b ;").unwrap();
+        assert_eq!(f.dict.code.len(), 9);
+        assert!(f.eval(": d c c").is_err());
+        assert_eq!(f.dict.code.len(), 9);
+    }
+
+    #[test]
+    fn failed_call_unwinds_return_stack() {
+        let mut f = Forth::new();
+        f.eval(": a 2 0 do drop loop ;").unwrap();
+        f.eval(": b a ;").unwrap();
+        assert_eq!(Err(Error::StackUnderflow), f.eval("1 b"));
+        assert!(f.rstack.is_empty());
+        assert!(f.loops.is_empty());
+    }
 }
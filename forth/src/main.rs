@@ -0,0 +1,196 @@
+//! Interactive Forth interpreter.
+//!
+//! `forth` starts a REPL showing the stack after every line. `forth FILE...`
+//! evaluates the files in order instead, as does piping a script into stdin.
+//! Besides Forth itself, a line may be `include FILE`, or `trace on|off` to
+//! print every instruction of compiled words as it runs, or `step on|off` to
+//! also wait for Enter before each one (`q` abandons the line).
+
+use std::io::{self, IsTerminal, Read};
+use std::path::PathBuf;
+use std::{env, fmt, fs, process};
+
+use forth::{Diagnostic, Forth};
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+
+#[derive(Default)]
+struct Repl {
+    forth: Forth,
+    // lines of a definition that isn't finished yet
+    pending: String,
+    // files being included, outermost first
+    including: Vec<PathBuf>,
+}
+
+/// Why `Repl::line` failed.
+enum Failure {
+    /// Evaluating Forth failed; positions are relative to the evaluated block.
+    Eval(Diagnostic),
+    /// A REPL command failed.
+    Command(String),
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Eval(d) => d.fmt(f),
+            Failure::Command(e) => f.write_str(e),
+        }
+    }
+}
+
+impl Repl {
+    /// Feeds one line of input, evaluating it once any open definition or
+    /// string is complete.
+    fn line(&mut self, line: &str) -> Result<(), Failure> {
+        self.pending.push_str(line);
+        self.pending.push('\n');
+        if Forth::is_incomplete(&self.pending) {
+            return Ok(());
+        }
+        let input = std::mem::take(&mut self.pending);
+        let mut words = input.split_whitespace();
+        let command = words.next().map(str::to_ascii_lowercase);
+        match (command.as_deref(), words.next(), words.next()) {
+            (Some("include"), Some(path), None) => self.include(path).map_err(Failure::Command),
+            (Some("trace"), Some(mode), None) => {
+                self.tracing(mode, false).map_err(Failure::Command)
+            }
+            (Some("step"), Some(mode), None) => self.tracing(mode, true).map_err(Failure::Command),
+            // a failing line doesn't change the session
+            _ => (self.forth)
+                .eval_atomic_diagnostic(&input)
+                .map_err(Failure::Eval),
+        }
+    }
+
+    fn tracing(&mut self, mode: &str, step: bool) -> Result<(), String> {
+        match mode {
+            "on" => self.forth.set_trace(move |event| {
+                eprintln!("{event}");
+                !step || proceed()
+            }),
+            "off" => self.forth.clear_trace(),
+            _ => return Err(format!("expected `on` or `off`, not `{mode}`")),
+        }
+        Ok(())
+    }
+
+    fn include(&mut self, path: &str) -> Result<(), String> {
+        let file = fs::canonicalize(path).map_err(|e| format!("{path}: {e}"))?;
+        if self.including.contains(&file) {
+            return Err(format!("{path}: included recursively"));
+        }
+        let text = fs::read_to_string(&file).map_err(|e| format!("{path}: {e}"))?;
+        self.including.push(file);
+        let res = self.script(&text).map_err(|e| format!("{path}:{e}"));
+        self.including.pop();
+        res
+    }
+
+    /// Evaluates `text` line by line, stopping at the first error, which is
+    /// reported as `LINE: message`, or `LINE:COLUMN: message` for Forth
+    /// errors.
+    fn script(&mut self, text: &str) -> Result<(), String> {
+        // first line of the block being read
+        let mut start = 1;
+        for (i, line) in text.lines().enumerate() {
+            if self.pending.is_empty() {
+                start = i + 1;
+            }
+            self.line(line).map_err(|e| match e {
+                Failure::Eval(d) => located(start + d.line - 1, &d),
+                Failure::Command(e) => format!("{}: {e}", i + 1),
+            })?;
+        }
+        if !self.pending.is_empty() {
+            self.pending.clear();
+            return Err(format!("{}: unterminated definition", text.lines().count()));
+        }
+        Ok(())
+    }
+
+    fn stack(&self) -> String {
+        let stack = self.forth.stack();
+        let mut s = format!("<{}>", stack.len());
+        for v in stack {
+            s += &format!(" {v}");
+        }
+        s
+    }
+}
+
+// `d` at script line `line`, in place of its line within the block
+fn located(line: usize, d: &Diagnostic) -> String {
+    let mut s = format!("{line}:{}: {}: `{}`", d.column, d.error, d.token);
+    if !d.trace.is_empty() {
+        s += &format!(" (in {})", d.trace.join(" -> "));
+    }
+    s
+}
+
+// waits for the user between steps, false if they asked to stop
+fn proceed() -> bool {
+    eprint!("  [Enter: step, q: stop] ");
+    let mut line = String::new();
+    match io::stdin().read_line(&mut line) {
+        Ok(_) => !line.trim().eq_ignore_ascii_case("q"),
+        Err(_) => false,
+    }
+}
+
+fn history() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".forth_history"))
+}
+
+fn interactive(repl: &mut Repl) -> rustyline::Result<()> {
+    let mut editor = DefaultEditor::new()?;
+    let history = history();
+    if let Some(path) = &history {
+        // there is no history yet on the first run
+        let _ = editor.load_history(path);
+    }
+    loop {
+        let prompt = if repl.pending.is_empty() { "> " } else { ". " };
+        match editor.readline(prompt) {
+            Ok(line) => {
+                editor.add_history_entry(line.as_str())?;
+                match repl.line(&line) {
+                    Ok(()) if repl.pending.is_empty() => println!(" ok {}", repl.stack()),
+                    Ok(()) => {}
+                    Err(e) => println!("error: {e}"),
+                }
+            }
+            // ^C abandons the current definition
+            Err(ReadlineError::Interrupted) => repl.pending.clear(),
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e),
+        }
+    }
+    if let Some(path) = &history {
+        editor.save_history(path)?;
+    }
+    Ok(())
+}
+
+fn main() {
+    let mut repl = Repl::default();
+    let files: Vec<String> = env::args().skip(1).collect();
+
+    let res = if !files.is_empty() {
+        files.iter().try_for_each(|file| repl.include(file))
+    } else if !io::stdin().is_terminal() {
+        let mut text = String::new();
+        match io::stdin().read_to_string(&mut text) {
+            Ok(_) => repl.script(&text).map_err(|e| format!("<stdin>:{e}")),
+            Err(e) => Err(format!("<stdin>: {e}")),
+        }
+    } else {
+        interactive(&mut repl).map_err(|e| e.to_string())
+    };
+    if let Err(e) = res {
+        eprintln!("error: {e}");
+        process::exit(1);
+    }
+}
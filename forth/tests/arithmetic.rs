@@ -0,0 +1,70 @@
+use forth::{Error, Forth, Value};
+
+#[test]
+fn modulo() {
+    let mut f = Forth::new();
+    assert!(f.eval("13 4 mod -13 4 mod").is_ok());
+    assert_eq!(vec![1, -1], f.stack());
+}
+
+#[test]
+fn divmod_pushes_remainder_then_quotient() {
+    let mut f = Forth::new();
+    assert!(f.eval("13 4 /mod").is_ok());
+    assert_eq!(vec![1, 3], f.stack());
+}
+
+#[test]
+fn division_words_by_zero() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::DivisionByZero), f.eval("4 0 mod"));
+    assert_eq!(Err(Error::DivisionByZero), f.eval("4 0 /mod"));
+    assert_eq!(Err(Error::StackUnderflow), f.eval("0 mod"));
+}
+
+#[test]
+fn negate_and_abs() {
+    let mut f = Forth::new();
+    assert!(f.eval("5 negate -7 abs 7 abs").is_ok());
+    assert_eq!(vec![-5, 7, 7], f.stack());
+}
+
+#[test]
+fn min_and_max() {
+    let mut f = Forth::new();
+    assert!(f.eval("3 -2 min 3 -2 max").is_ok());
+    assert_eq!(vec![-2, 3], f.stack());
+    assert_eq!(Err(Error::StackUnderflow), Forth::new().eval("1 min"));
+}
+
+#[test]
+fn overflow_is_an_error() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::Overflow), f.eval(&format!("{} 1 +", Value::MAX)));
+    assert_eq!(Err(Error::Overflow), f.eval(&format!("{} 1 -", Value::MIN)));
+    assert_eq!(Err(Error::Overflow), f.eval(&format!("{} 2 *", Value::MAX)));
+    assert_eq!(
+        Err(Error::Overflow),
+        f.eval(&format!("{} -1 /", Value::MIN))
+    );
+    assert_eq!(
+        Err(Error::Overflow),
+        f.eval(&format!("{} negate", Value::MIN))
+    );
+    assert_eq!(Err(Error::Overflow), f.eval(&format!("{} abs", Value::MIN)));
+}
+
+#[test]
+fn overflowing_literal_is_an_error() {
+    let mut f = Forth::new();
+    let literal = format!("{}0", Value::MAX);
+    assert_eq!(Err(Error::Overflow), f.eval(&literal));
+    assert_eq!(Err(Error::Overflow), f.eval(&format!(": foo {literal} ;")));
+}
+
+#[test]
+fn extremes_fit_in_a_cell() {
+    let mut f = Forth::new();
+    assert!(f.eval(&format!("{} {}", Value::MIN, Value::MAX)).is_ok());
+    assert_eq!(vec![Value::MIN, Value::MAX], f.stack());
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use common::forth;
+use forth::{Error, Forth};
+
+#[test]
+fn failed_eval_leaves_partial_state() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::UnknownWord), f.eval("1 2 : foo 3 ; oops"));
+    assert_eq!(vec![1, 2], f.stack());
+    assert!(f.eval("foo").is_ok());
+}
+
+#[test]
+fn eval_atomic_rolls_back_stack_and_dictionary() {
+    let mut f = Forth::new();
+    assert!(f.eval("10 : foo 1 ;").is_ok());
+    assert_eq!(
+        Err(Error::DivisionByZero),
+        f.eval_atomic("drop 20 : foo 2 ; : bar 3 ; 1 0 /")
+    );
+    assert_eq!(vec![10], f.stack());
+    assert!(f.eval("foo").is_ok());
+    assert_eq!(vec![10, 1], f.stack());
+    assert_eq!(Err(Error::UnknownWord), f.eval("bar"));
+}
+
+#[test]
+fn eval_atomic_rolls_back_memory() {
+    let mut f = Forth::new();
+    assert!(f.eval("variable x 5 x !").is_ok());
+    assert!(f.eval_atomic("7 x ! 3 allot variable y nope").is_err());
+    assert!(f.eval("x @ here").is_ok());
+    assert_eq!(vec![5, 1], f.stack());
+}
+
+#[test]
+fn eval_atomic_keeps_successful_changes() {
+    let mut f = Forth::new();
+    assert!(f.eval_atomic("1 : foo 2 ;").is_ok());
+    assert!(f.eval("foo").is_ok());
+    assert_eq!(vec![1, 2], f.stack());
+}
+
+#[test]
+fn checkpoint_and_restore() {
+    let mut f = Forth::new();
+    assert!(f.eval("1 : foo 2 ;").is_ok());
+    let saved = f.checkpoint();
+    assert!(f.eval("drop : foo 3 ; : bar 4 ; foo").is_ok());
+    assert_eq!(vec![3], f.stack());
+
+    assert!(f.restore(&saved).is_ok());
+    assert_eq!(vec![1], f.stack());
+    assert!(f.eval("foo").is_ok());
+    assert_eq!(vec![1, 2], f.stack());
+    assert_eq!(Err(Error::UnknownWord), f.eval("bar"));
+
+    // a checkpoint can be restored more than once
+    assert!(f.restore(&saved).is_ok());
+    assert_eq!(vec![1], f.stack());
+}
+
+#[test]
+fn eval_atomic_diagnostic_reports_the_position() {
+    let mut f = Forth::new();
+    assert!(f.eval("1").is_ok());
+    let d = f.eval_atomic_diagnostic("2 : foo 3 ;\n  oops").unwrap_err();
+    assert_eq!(Error::UnknownWord, d.error);
+    assert_eq!((2, 3), (d.line, d.column));
+    assert_eq!(vec![1], f.stack());
+    assert_eq!(Err(Error::UnknownWord), f.eval("foo"));
+}
+
+#[test]
+fn restore_brings_back_interpreted_strings() {
+    let (mut f, out) = forth();
+    assert!(f.eval(r#"s" one""#).is_ok());
+    let saved = f.checkpoint();
+    assert!(f.eval(r#"2drop s" two" 2drop s" three" 2drop"#).is_ok());
+    assert!(f.restore(&saved).is_ok());
+    assert!(f.eval(r#"type s" four" type"#).is_ok());
+    assert_eq!("onefour", out.take());
+}
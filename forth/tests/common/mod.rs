@@ -0,0 +1,39 @@
+// Helpers shared by the integration tests; each test crate uses only some.
+#![allow(dead_code)]
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use forth::{Forth, Value};
+
+/// Shared buffer standing in for the output sink.
+#[derive(Clone, Default)]
+pub struct Capture(Arc<Mutex<Vec<u8>>>);
+
+impl Capture {
+    pub fn take(&self) -> String {
+        String::from_utf8(std::mem::take(&mut *self.0.lock().unwrap())).unwrap()
+    }
+}
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+pub fn forth() -> (Forth, Capture) {
+    let out = Capture::default();
+    (Forth::with_output(out.clone()), out)
+}
+
+/// The stack left by evaluating `input` on a fresh interpreter.
+pub fn run(input: &str) -> Vec<Value> {
+    let mut f = Forth::new();
+    assert!(f.eval(input).is_ok(), "{input}");
+    f.stack().to_vec()
+}
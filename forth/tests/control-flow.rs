@@ -0,0 +1,119 @@
+use forth::{Error, Forth};
+
+#[test]
+fn if_then() {
+    let mut f = Forth::new();
+    assert!(f.eval(": ?double if 2 * then ;").is_ok());
+    assert!(f.eval("3 1 ?double 3 0 ?double").is_ok());
+    assert_eq!(vec![6, 3], f.stack());
+}
+
+#[test]
+fn if_else_then() {
+    let mut f = Forth::new();
+    assert!(f.eval(": pick-one if 10 else 20 then ;").is_ok());
+    assert!(f.eval("1 pick-one 0 pick-one -1 pick-one").is_ok());
+    assert_eq!(vec![10, 20, 10], f.stack());
+}
+
+#[test]
+fn nested_if() {
+    let mut f = Forth::new();
+    assert!(f
+        .eval(": sign dup if 0 swap - if -1 else 1 then else drop 0 then ;")
+        .is_ok());
+    assert!(f.eval("5 sign 0 sign").is_ok());
+    assert_eq!(vec![-1, 0], f.stack());
+}
+
+#[test]
+fn do_loop_with_index() {
+    let mut f = Forth::new();
+    assert!(f.eval(": count 5 0 do i loop ;").is_ok());
+    assert!(f.eval("count").is_ok());
+    assert_eq!(vec![0, 1, 2, 3, 4], f.stack());
+}
+
+#[test]
+fn do_loop_is_skipped_when_start_reaches_limit() {
+    let mut f = Forth::new();
+    assert!(f.eval(": none 3 3 do i loop ;").is_ok());
+    assert!(f.eval("none").is_ok());
+    assert!(f.stack().is_empty());
+}
+
+#[test]
+fn nested_loops_with_j() {
+    let mut f = Forth::new();
+    assert!(f
+        .eval(": grid 2 0 do 3 0 do j 10 * i + loop loop ;")
+        .is_ok());
+    assert!(f.eval("grid").is_ok());
+    assert_eq!(vec![0, 1, 2, 10, 11, 12], f.stack());
+}
+
+#[test]
+fn leave_exits_innermost_loop() {
+    let mut f = Forth::new();
+    assert!(f
+        .eval(": upto-3 10 0 do i dup 3 - 0 swap - if else leave then loop ;")
+        .is_ok());
+    assert!(f.eval("upto-3").is_ok());
+    assert_eq!(vec![0, 1, 2, 3], f.stack());
+}
+
+#[test]
+fn begin_until() {
+    let mut f = Forth::new();
+    assert!(f
+        .eval(": countdown begin dup 1 - dup if 0 else -1 then until ;")
+        .is_ok());
+    assert!(f.eval("3 countdown").is_ok());
+    assert_eq!(vec![3, 2, 1, 0], f.stack());
+}
+
+#[test]
+fn begin_while_repeat() {
+    let mut f = Forth::new();
+    assert!(f.eval(": halve begin dup while 2 / repeat ;").is_ok());
+    assert!(f.eval("100 halve").is_ok());
+    assert_eq!(vec![0], f.stack());
+}
+
+#[test]
+fn control_words_are_case_insensitive() {
+    let mut f = Forth::new();
+    assert!(f.eval(": foo 3 0 DO I If 1 ELSE 2 Then LOOP ;").is_ok());
+    assert!(f.eval("foo").is_ok());
+    assert_eq!(vec![2, 1, 1], f.stack());
+}
+
+#[test]
+fn unbalanced_control_structures() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::UnbalancedControl), f.eval(": foo 1 if 2 ;"));
+    assert_eq!(Err(Error::UnbalancedControl), f.eval(": foo 1 then ;"));
+    assert_eq!(Err(Error::UnbalancedControl), f.eval(": foo 1 if 2 loop ;"));
+    assert_eq!(
+        Err(Error::UnbalancedControl),
+        f.eval(": foo 5 0 do i until ;")
+    );
+    assert_eq!(
+        Err(Error::UnbalancedControl),
+        f.eval(": foo begin 1 while 2 until ;")
+    );
+    assert_eq!(Err(Error::UnbalancedControl), f.eval(": foo i ;"));
+    assert_eq!(
+        Err(Error::UnbalancedControl),
+        f.eval(": foo 2 0 do j loop ;")
+    );
+    assert_eq!(Err(Error::UnbalancedControl), f.eval(": foo leave ;"));
+    assert_eq!(Err(Error::InvalidWord), f.eval(": foo 1 if 2 then"));
+}
+
+#[test]
+fn control_words_are_compile_only() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::CompileOnly), f.eval("1 if 2 then"));
+    assert_eq!(Err(Error::CompileOnly), f.eval("i"));
+}
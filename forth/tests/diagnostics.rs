@@ -0,0 +1,76 @@
+use forth::{Error, Forth};
+
+#[test]
+fn error_reports_token_and_position() {
+    let mut f = Forth::new();
+    let d = f.eval_diagnostic("1 2 +\n  3 foo 4").unwrap_err();
+    assert_eq!(Error::UnknownWord, d.error);
+    assert_eq!("foo", d.token);
+    assert_eq!(10, d.offset);
+    assert_eq!((2, 5), (d.line, d.column));
+    assert!(d.trace.is_empty());
+}
+
+#[test]
+fn trace_lists_active_user_words() {
+    let mut f = Forth::new();
+    assert!(f
+        .eval(": inner 0 / ; : middle 1 inner ; : outer middle ;")
+        .is_ok());
+    let d = f.eval_diagnostic("5 outer").unwrap_err();
+    assert_eq!(Error::DivisionByZero, d.error);
+    assert_eq!("outer", d.token);
+    assert_eq!(2, d.offset);
+    assert_eq!(vec!["outer", "middle", "inner"], d.trace);
+}
+
+#[test]
+fn trace_is_cleared_between_evals() {
+    let mut f = Forth::new();
+    assert!(f.eval(": boom drop ;").is_ok());
+    assert!(!f.eval_diagnostic("boom").unwrap_err().trace.is_empty());
+    assert!(f.eval_diagnostic("drop").unwrap_err().trace.is_empty());
+}
+
+#[test]
+fn compile_errors_point_into_the_definition() {
+    let mut f = Forth::new();
+    let d = f.eval_diagnostic(": foo 1 bar ;").unwrap_err();
+    assert_eq!(Error::InvalidWord, d.error);
+    assert_eq!("bar", d.token);
+    assert_eq!((1, 9), (d.line, d.column));
+}
+
+#[test]
+fn columns_count_characters() {
+    let mut f = Forth::with_output(std::io::sink());
+    let d = f.eval_diagnostic(r#"." héllo" nope"#).unwrap_err();
+    assert_eq!("nope", d.token);
+    assert_eq!(11, d.offset);
+    assert_eq!(11, d.column);
+}
+
+#[test]
+fn display() {
+    let mut f = Forth::new();
+    assert!(f.eval(": inner drop ; : outer inner ;").is_ok());
+    let d = f.eval_diagnostic("\nouter").unwrap_err();
+    assert_eq!(
+        "stack underflow at line 2, column 1: `outer` (in outer -> inner)",
+        d.to_string()
+    );
+    assert_eq!("division by zero", Error::DivisionByZero.to_string());
+}
+
+#[test]
+fn is_a_std_error() {
+    let mut f = Forth::new();
+    let d: Box<dyn std::error::Error> = Box::new(f.eval_diagnostic("+").unwrap_err());
+    assert_eq!("stack underflow", d.source().unwrap().to_string());
+}
+
+#[test]
+fn eval_returns_the_plain_error() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::UnknownWord), f.eval("1 foo"));
+}
@@ -0,0 +1,135 @@
+use forth::{Error, Forth, ForthLimits};
+
+fn image(f: &Forth) -> Vec<u8> {
+    let mut buf = vec![];
+    assert!(f.save(&mut buf).is_ok());
+    buf
+}
+
+const SESSION: &str = r#"
+    : foo 5 ;
+    : bar foo ;
+    : foo foo 1 + ;
+    variable x 42 x !
+    create table 1 , 2 , 3 ,
+    : greet ." hello" ;
+    : sum 0 swap 0 do i + loop ;
+    -7 100000000
+"#;
+
+#[test]
+fn round_trip() {
+    let mut f = Forth::new();
+    assert!(f.eval(SESSION).is_ok());
+    let mut g = Forth::new();
+    assert!(g.load(image(&f).as_slice()).is_ok());
+    assert_eq!(f.stack(), g.stack());
+    assert_eq!(image(&f), image(&g));
+    assert_eq!(f.words(), g.words());
+    for name in f.words() {
+        assert_eq!(f.see(name), g.see(name));
+    }
+    assert!(g.eval("bar foo x @ table 2 + @ 4 sum").is_ok());
+    assert_eq!(g.stack(), [-7, 100000000, 5, 6, 42, 3, 6]);
+}
+
+#[test]
+fn load_replaces_the_session() {
+    let mut f = Forth::new();
+    assert!(f.eval(": a 1 ; 9").is_ok());
+    let saved = image(&f);
+    assert!(f.eval(": b 2 ; variable v 3").is_ok());
+    assert!(f.load(saved.as_slice()).is_ok());
+    assert_eq!(f.stack(), [9]);
+    assert_eq!(vec!["a"], f.words());
+    assert!(f.eval("here").is_ok());
+    assert_eq!(f.stack(), [9, 0]);
+}
+
+#[test]
+fn native_words_are_found_by_name() {
+    let mut f = Forth::new();
+    assert!(f.define_native("seven", |f| f.push(7)).is_ok());
+    assert!(f.eval(": twice seven seven ;").is_ok());
+    let saved = image(&f);
+
+    let mut g = Forth::new();
+    assert_eq!(Err(Error::UnknownWord), g.load(saved.as_slice()));
+    assert!(g.define_native("seven", |f| f.push(-7)).is_ok());
+    assert!(g.load(saved.as_slice()).is_ok());
+    assert!(g.eval("twice").is_ok());
+    assert_eq!(g.stack(), [-7, -7]);
+}
+
+#[test]
+fn invalid_images_change_nothing() {
+    let mut f = Forth::new();
+    assert!(f.eval(SESSION).is_ok());
+    let saved = image(&f);
+
+    let mut g = Forth::new();
+    assert!(g.eval(": keep 1 ; 2").is_ok());
+    assert_eq!(Err(Error::InvalidImage), g.load(&b"FRTX"[..]));
+    assert_eq!(Err(Error::InvalidImage), g.load(&saved[..saved.len() - 1]));
+    let mut longer = saved.clone();
+    longer.push(0);
+    assert_eq!(Err(Error::InvalidImage), g.load(longer.as_slice()));
+    assert_eq!(g.stack(), [2]);
+    assert_eq!(vec!["keep"], g.words());
+}
+
+#[test]
+fn corrupted_images_never_panic() {
+    let mut f = Forth::new();
+    assert!(f.eval(SESSION).is_ok());
+    let saved = image(&f);
+    let limits = ForthLimits {
+        max_instructions: 10_000,
+        ..Default::default()
+    };
+    for at in 5..saved.len() {
+        for byte in [0, 1, 0x7f, 0xff] {
+            let mut bad = saved.clone();
+            bad[at] = byte;
+            let mut g = Forth::with_limits(limits);
+            if g.load(bad.as_slice()).is_ok() {
+                for name in g.words().iter().map(|w| w.to_string()).collect::<Vec<_>>() {
+                    let _ = g.see(&name);
+                    let _ = g.eval(&name);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn load_respects_limits() {
+    let mut f = Forth::new();
+    assert!(f.eval(": a ; : b ; : c ; 1 2 3").is_ok());
+    let saved = image(&f);
+    let mut g = Forth::with_limits(ForthLimits {
+        max_words: 2,
+        ..Default::default()
+    });
+    assert_eq!(Err(Error::DictionaryFull), g.load(saved.as_slice()));
+    let mut g = Forth::with_limits(ForthLimits {
+        max_stack_depth: 2,
+        ..Default::default()
+    });
+    assert_eq!(Err(Error::InvalidImage), g.load(saved.as_slice()));
+}
+
+#[test]
+fn strings_outside_the_data_space_are_rejected() {
+    let mut f = Forth::new();
+    assert!(f.eval(r#": s s" hi" ;"#).is_ok());
+    let saved = image(&f);
+    // the code ends with the string's address and length, then a return
+    assert!(saved.ends_with(&[16, 0, 2, 3]));
+    let mut bad = saved[..saved.len() - 3].to_vec();
+    bad.extend([0xff; 9]);
+    bad.extend([0x01, 2, 3]);
+    let mut g = Forth::new();
+    assert_eq!(Err(Error::InvalidImage), g.load(bad.as_slice()));
+    assert_eq!(None, g.see("s"));
+}
@@ -0,0 +1,22 @@
+use forth::Forth;
+
+#[test]
+fn incomplete_input() {
+    assert!(Forth::is_incomplete(": foo"));
+    assert!(Forth::is_incomplete(": foo 1\n2"));
+    assert!(Forth::is_incomplete(r#"." open"#));
+    assert!(!Forth::is_incomplete(": foo 1 ;"));
+    assert!(!Forth::is_incomplete(r#": foo ." ; " ;"#));
+    assert!(Forth::is_incomplete(r#": foo ." ; " "#));
+    assert!(!Forth::is_incomplete("1 2 +"));
+}
+
+#[test]
+fn names_and_characters_do_not_open_definitions() {
+    for input in ["char : emit", "see :", "forget ;", "variable :", "create :"] {
+        assert!(!Forth::is_incomplete(input), "{input}");
+    }
+    assert!(!Forth::is_incomplete(": colon [char] : ; colon emit"));
+    assert!(Forth::is_incomplete(": semi [char] ;"));
+    assert!(!Forth::is_incomplete("1 constant ; 2"));
+}
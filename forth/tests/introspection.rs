@@ -0,0 +1,106 @@
+mod common;
+
+use common::forth;
+use forth::{Error, Forth};
+
+#[test]
+fn words_lists_newest_first_without_shadowed() {
+    let mut f = Forth::new();
+    assert!(f.eval(": foo 1 ; : bar foo ; variable x : foo 2 ;").is_ok());
+    assert_eq!(vec!["foo", "x", "bar"], f.words());
+}
+
+#[test]
+fn see_round_trips_simple_definitions() {
+    let mut f = Forth::new();
+    assert!(f.eval(": sq dup * ; : quad SQ sq -1 + ;").is_ok());
+    assert_eq!(Some(": sq dup * ;".to_string()), f.see("sq"));
+    assert_eq!(Some(": quad sq sq -1 + ;".to_string()), f.see("QUAD"));
+    assert_eq!(None, f.see("nope"));
+    assert_eq!(None, f.see("+"));
+}
+
+#[test]
+fn see_restores_control_structures() {
+    let sources = [
+        ": a if 1 then ;",
+        ": b if 1 else 2 then ;",
+        ": c if if 1 else then then ;",
+        ": d begin 1 - dup until ;",
+        ": e begin dup while 1 - repeat ;",
+        ": f 10 0 do i 5 if leave then loop ;",
+        ": g begin begin 1 until 0 until ;",
+        ": h 3 0 do 2 0 do i j + loop loop if begin 0 until then ;",
+        r#": i ." hello world" cr ;"#,
+    ];
+    let mut f = Forth::new();
+    for src in sources {
+        assert!(f.eval(src).is_ok(), "{src}");
+        let name = src.split_whitespace().nth(1).unwrap();
+        assert_eq!(Some(src.to_string()), f.see(name));
+    }
+}
+
+#[test]
+fn see_lists_redefined_words_first() {
+    let mut f = Forth::new();
+    assert!(f
+        .eval(": foo 5 ; : bar foo ; : foo foo 1 + ; : baz foo bar ;")
+        .is_ok());
+    assert_eq!(Some(": foo 5 ;\n: foo foo 1 + ;".to_string()), f.see("foo"));
+    assert_eq!(
+        Some(": foo 5 ;\n: foo foo 1 + ;\n: baz foo bar ;".to_string()),
+        f.see("baz")
+    );
+    assert_eq!(Some(": foo 5 ;\n: bar foo ;".to_string()), f.see("bar"));
+    assert!(f.eval(": bar 0 ;").is_ok());
+    assert_eq!(
+        Some(": foo 5 ;\n: bar foo ;\n: foo foo 1 + ;\n: baz foo bar ;".to_string()),
+        f.see("baz")
+    );
+}
+
+#[test]
+fn forth_words_write_to_output() {
+    let (mut f, out) = forth();
+    assert!(f
+        .eval(": sq dup * ; variable x words see sq see + see x")
+        .is_ok());
+    assert_eq!("x sq\n: sq dup * ;\n+ is a builtin\n: x 0 ;\n", out.take());
+    assert_eq!(Err(Error::UnknownWord), f.eval("see nope"));
+    assert_eq!(Err(Error::InvalidWord), f.eval("see"));
+}
+
+#[test]
+fn forget_removes_later_words() {
+    let mut f = Forth::new();
+    assert!(f.eval(": foo 1 ; : bar 2 ; : baz 3 ; forget bar").is_ok());
+    assert_eq!(vec!["foo"], f.words());
+    assert_eq!(Err(Error::UnknownWord), f.eval("baz"));
+    assert_eq!(Err(Error::UnknownWord), f.forget("bar"));
+    assert!(f.eval("foo").is_ok());
+    assert_eq!(f.stack(), [1]);
+}
+
+#[test]
+fn forget_uncovers_shadowed_definitions() {
+    let mut f = Forth::new();
+    assert!(f.eval(": foo 1 ; : foo 2 ; FORGET Foo foo").is_ok());
+    assert_eq!(f.stack(), [1]);
+    assert!(f.forget("foo").is_ok());
+    assert!(f.words().is_empty());
+    assert_eq!(Err(Error::UnknownWord), f.eval("forget dup"));
+}
+
+#[test]
+fn forget_releases_data_space() {
+    let mut f = Forth::new();
+    assert!(f
+        .eval("variable a variable b 5 allot forget b here")
+        .is_ok());
+    assert_eq!(f.stack(), [1]);
+    assert!(f
+        .eval(r#": hi ." hi" ; forget hi : ho ." ho" ; ho"#)
+        .is_ok());
+    assert_eq!(Some(r#": ho ." ho" ;"#.to_string()), f.see("ho"));
+}
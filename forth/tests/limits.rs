@@ -0,0 +1,87 @@
+use forth::{Error, Forth, ForthLimits};
+
+#[test]
+fn unlimited_by_default() {
+    assert_eq!(ForthLimits::default(), Forth::new().limits());
+}
+
+#[test]
+fn stack_depth() {
+    let mut f = Forth::with_limits(ForthLimits {
+        max_stack_depth: 3,
+        ..Default::default()
+    });
+    assert!(f.eval("1 2 3").is_ok());
+    assert_eq!(Err(Error::StackOverflow), f.eval("4"));
+    assert_eq!(Err(Error::StackOverflow), f.eval("dup"));
+    assert_eq!(vec![1, 2, 3], f.stack());
+}
+
+#[test]
+fn runaway_stack_growth_in_a_loop() {
+    let mut f = Forth::with_limits(ForthLimits {
+        max_stack_depth: 100,
+        ..Default::default()
+    });
+    assert!(f.eval(": fill begin 1 0 until ;").is_ok());
+    assert_eq!(Err(Error::StackOverflow), f.eval("fill"));
+    assert_eq!(100, f.stack().len());
+}
+
+#[test]
+fn instruction_budget_stops_infinite_loops() {
+    let mut f = Forth::with_limits(ForthLimits {
+        max_instructions: 1000,
+        ..Default::default()
+    });
+    assert!(f.eval(": forever begin 0 until ;").is_ok());
+    assert_eq!(Err(Error::InstructionLimit), f.eval("forever"));
+}
+
+#[test]
+fn instruction_budget_is_per_eval() {
+    let mut f = Forth::with_limits(ForthLimits {
+        max_instructions: 10,
+        ..Default::default()
+    });
+    assert!(f.eval(": five 1 2 3 4 ;").is_ok());
+    assert!(f.eval("five").is_ok());
+    assert!(f.eval("five").is_ok());
+    assert_eq!(Err(Error::InstructionLimit), f.eval("five five five"));
+}
+
+#[test]
+fn dictionary_size() {
+    let mut f = Forth::with_limits(ForthLimits {
+        max_words: 2,
+        ..Default::default()
+    });
+    assert!(f.eval(": a 1 ; variable b").is_ok());
+    assert_eq!(Err(Error::DictionaryFull), f.eval(": a 2 ;"));
+    assert_eq!(Err(Error::DictionaryFull), f.eval("5 constant c"));
+    assert!(f.eval("a b").is_ok());
+}
+
+#[test]
+fn definition_length() {
+    let mut f = Forth::with_limits(ForthLimits {
+        max_definition_len: 3,
+        ..Default::default()
+    });
+    assert!(f.eval(": ok 1 2 + ;").is_ok());
+    assert_eq!(Err(Error::DefinitionTooLong), f.eval(": long 1 2 3 4 ;"));
+    assert_eq!(Err(Error::UnknownWord), f.eval("long"));
+    assert!(f.eval("ok").is_ok());
+    assert_eq!(vec![3], f.stack());
+}
+
+#[test]
+fn limits_can_be_changed() {
+    let mut f = Forth::new();
+    assert!(f.eval("1 2").is_ok());
+    f.set_limits(ForthLimits {
+        max_stack_depth: 2,
+        ..Default::default()
+    });
+    assert_eq!(Err(Error::StackOverflow), f.eval("3"));
+}
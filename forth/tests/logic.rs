@@ -0,0 +1,92 @@
+mod common;
+
+use common::run;
+use forth::{Error, Forth, Value};
+
+#[test]
+fn equality() {
+    assert_eq!(run("1 1 = 1 2 = -3 -3 ="), [-1, 0, -1]);
+    assert_eq!(run("1 1 <> 1 2 <>"), [0, -1]);
+}
+
+#[test]
+fn ordering_uses_the_second_value_first() {
+    assert_eq!(run("1 2 < 2 1 < 2 2 <"), [-1, 0, 0]);
+    assert_eq!(run("1 2 > 2 1 > 2 2 >"), [0, -1, 0]);
+    assert_eq!(run("-5 3 < 3 -5 >"), [-1, -1]);
+}
+
+#[test]
+fn zero_equals() {
+    assert_eq!(run("0 0= 7 0= -1 0="), [-1, 0, 0]);
+}
+
+#[test]
+fn bitwise() {
+    assert_eq!(run("12 10 and 12 10 or 12 10 xor"), [8, 14, 6]);
+    assert_eq!(run("0 invert -1 invert 5 invert"), [-1, 0, -6]);
+    assert_eq!(run("-1 0 and -1 -1 or"), [0, -1]);
+}
+
+#[test]
+fn flags_combine_with_bitwise_words() {
+    assert_eq!(run("1 2 < 3 4 > or 1 2 < 3 4 > and"), [-1, 0]);
+    assert_eq!(run("1 2 < invert"), [0]);
+}
+
+#[test]
+fn shifts() {
+    assert_eq!(
+        run("1 4 lshift 256 4 rshift 3 0 lshift 3 0 rshift"),
+        [16, 16, 3, 3]
+    );
+    let bits = Value::BITS;
+    assert_eq!(run(&format!("-1 {} rshift", bits - 1)), [1]);
+    assert_eq!(run(&format!("1 {} lshift", bits - 1)), [Value::MIN]);
+    assert_eq!(run("-1 1 rshift"), [Value::MAX]);
+}
+
+#[test]
+fn out_of_range_shifts_give_zero() {
+    let bits = Value::BITS;
+    assert_eq!(run(&format!("1 {bits} lshift -1 {bits} rshift")), [0, 0]);
+    assert_eq!(run("1 -1 lshift 1 -1 rshift"), [0, 0]);
+}
+
+#[test]
+fn underflow() {
+    for word in ["=", "<", ">", "<>", "and", "or", "xor", "lshift", "rshift"] {
+        assert_eq!(
+            Err(Error::StackUnderflow),
+            Forth::new().eval(&format!("1 {word}")),
+            "{word}"
+        );
+    }
+    for word in ["0=", "invert"] {
+        assert_eq!(Err(Error::StackUnderflow), Forth::new().eval(word));
+    }
+}
+
+#[test]
+fn conditions_in_definitions() {
+    let mut f = Forth::new();
+    assert!(f
+        .eval(": sign dup 0 < if drop -1 else 0 > if 1 else 0 then then ;")
+        .is_ok());
+    assert!(f.eval("-7 sign 0 sign 9 sign").is_ok());
+    assert_eq!(f.stack(), [-1, 0, 1]);
+}
+
+#[test]
+fn factorial_with_comparison() {
+    let mut f = Forth::new();
+    assert!(f
+        .eval(": fact dup 1 > if dup 1 - recurse * then ; 6 fact")
+        .is_ok());
+    assert_eq!(f.stack(), [720]);
+}
+
+#[test]
+fn case_insensitive() {
+    assert_eq!(run("6 3 AND 6 3 Or 1 3 LShift"), [2, 7, 8]);
+}
@@ -0,0 +1,94 @@
+use forth::{Error, Forth, DATA_SPACE};
+
+#[test]
+fn variable_store_and_fetch() {
+    let mut f = Forth::new();
+    assert!(f.eval("variable x 42 x ! x @").is_ok());
+    assert_eq!(vec![42], f.stack());
+}
+
+#[test]
+fn variables_start_at_zero_and_are_distinct() {
+    let mut f = Forth::new();
+    assert!(f.eval("variable a variable b 1 a ! a @ b @").is_ok());
+    assert_eq!(vec![1, 0], f.stack());
+}
+
+#[test]
+fn plus_store() {
+    let mut f = Forth::new();
+    assert!(f.eval("variable n 5 n ! 3 n +! n @").is_ok());
+    assert_eq!(vec![8], f.stack());
+}
+
+#[test]
+fn state_survives_between_evals() {
+    let mut f = Forth::new();
+    assert!(f.eval("variable counter").is_ok());
+    assert!(f.eval(": tick 1 counter +! ;").is_ok());
+    assert!(f.eval("tick tick").is_ok());
+    assert!(f.eval("tick counter @").is_ok());
+    assert_eq!(vec![3], f.stack());
+}
+
+#[test]
+fn constant() {
+    let mut f = Forth::new();
+    assert!(f
+        .eval("7 constant seven : double-seven seven 2 * ; double-seven")
+        .is_ok());
+    assert_eq!(vec![14], f.stack());
+    assert_eq!(
+        Err(Error::StackUnderflow),
+        Forth::new().eval("constant nothing")
+    );
+}
+
+#[test]
+fn create_allot_and_comma() {
+    let mut f = Forth::new();
+    assert!(f.eval("create table 10 , 20 , 30 ,").is_ok());
+    assert!(f.eval("table @ table 2 + @").is_ok());
+    assert_eq!(vec![10, 30], f.stack());
+    assert!(f.eval("create buf 4 allot here buf -").is_ok());
+    assert_eq!(vec![10, 30, 4], f.stack());
+}
+
+#[test]
+fn here_tracks_allotted_space() {
+    let mut f = Forth::new();
+    assert!(f.eval("here 3 allot here -2 allot here").is_ok());
+    assert_eq!(vec![0, 3, 1], f.stack());
+}
+
+#[test]
+fn invalid_addresses() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::InvalidAddress), f.eval("0 @"));
+    assert_eq!(Err(Error::InvalidAddress), f.eval("variable x -1 @"));
+    assert_eq!(Err(Error::InvalidAddress), f.eval("1 x 1 + !"));
+    assert_eq!(Err(Error::InvalidAddress), f.eval("-5 allot"));
+    assert_eq!(
+        Err(Error::StackUnderflow),
+        Forth::new().eval("variable x x !")
+    );
+}
+
+#[test]
+fn data_space_is_bounded() {
+    let mut f = Forth::new();
+    assert_eq!(
+        Err(Error::DataSpaceFull),
+        f.eval(&format!("{} allot", DATA_SPACE + 1))
+    );
+    assert!(f.eval(&format!("{DATA_SPACE} allot")).is_ok());
+    assert_eq!(Err(Error::DataSpaceFull), f.eval("1 ,"));
+    assert_eq!(Err(Error::DataSpaceFull), f.eval("variable x"));
+}
+
+#[test]
+fn defining_words_need_a_name() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::InvalidWord), f.eval("variable"));
+    assert_eq!(Err(Error::InvalidWord), f.eval("1 constant 2"));
+}
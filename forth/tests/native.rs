@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use forth::{Error, Forth, ForthLimits, Value};
+
+#[test]
+fn native_words_use_the_stack() {
+    let mut f = Forth::new();
+    assert!(f
+        .define_native("sum3", |f| {
+            let sum = f.pop()? + f.pop()? + f.pop()?;
+            f.push(sum)
+        })
+        .is_ok());
+    assert!(f.eval("1 2 3 sum3 : six 1 2 3 SUM3 ; six").is_ok());
+    assert_eq!(f.stack(), [6, 6]);
+    assert_eq!(Err(Error::StackUnderflow), f.eval("drop drop sum3"));
+}
+
+#[test]
+fn native_words_reach_the_context() {
+    let mut f = Forth::new();
+    f.set_context(HashMap::<Value, Value>::from([(1, 100), (2, 200)]));
+    assert!(f
+        .define_native("lookup", |f| {
+            let key = f.pop()?;
+            let db = f
+                .context::<HashMap<Value, Value>>()
+                .ok_or(Error::UnknownWord)?;
+            let value = *db.get(&key).ok_or(Error::InvalidAddress)?;
+            f.push(value)
+        })
+        .is_ok());
+    assert!(f.eval("2 lookup 1 lookup").is_ok());
+    assert_eq!(f.stack(), [200, 100]);
+    assert_eq!(Err(Error::InvalidAddress), f.eval("3 lookup"));
+    assert!(f.context::<String>().is_none());
+    assert!(f.set_context(()).is_some());
+    assert_eq!(Err(Error::UnknownWord), f.eval("1 lookup"));
+}
+
+#[test]
+fn closures_keep_their_captures() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counted = Arc::clone(&calls);
+    let mut f = Forth::new();
+    assert!(f
+        .define_native("tick", move |_| {
+            counted.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        })
+        .is_ok());
+    assert!(f.eval(": ticks 0 do tick loop ; 5 ticks tick").is_ok());
+    assert_eq!(6, calls.load(Ordering::Relaxed));
+}
+
+#[test]
+fn native_words_can_be_redefined() {
+    let mut f = Forth::new();
+    assert!(f.define_native("answer", |f| f.push(1)).is_ok());
+    assert!(f.eval(": old answer ;").is_ok());
+    assert!(f.define_native("answer", |f| f.push(42)).is_ok());
+    assert!(f.eval("old answer").is_ok());
+    assert_eq!(f.stack(), [1, 42]);
+    assert_eq!(None, f.see("answer"));
+    assert_eq!(Some(": old answer ;".to_string()), f.see("old"));
+    assert!(f.forget("answer").is_ok());
+    assert!(f.eval("answer").is_ok());
+    assert_eq!(f.stack(), [1, 42, 1]);
+}
+
+#[test]
+fn native_words_can_shadow_builtins() {
+    let mut f = Forth::new();
+    assert!(f.define_native("dup", |f| f.push(0)).is_ok());
+    assert!(f.eval("1 dup").is_ok());
+    assert_eq!(f.stack(), [1, 0]);
+}
+
+#[test]
+fn invalid_native_names() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::InvalidWord), f.define_native("12", |_| Ok(())));
+    assert_eq!(Err(Error::InvalidWord), f.define_native("", |_| Ok(())));
+    assert_eq!(Err(Error::InvalidWord), f.define_native("a b", |_| Ok(())));
+}
+
+#[test]
+fn native_words_respect_limits() {
+    let mut f = Forth::with_limits(ForthLimits {
+        max_stack_depth: 2,
+        max_words: 1,
+        ..Default::default()
+    });
+    assert!(f
+        .define_native("many", |f| (0..3).try_for_each(|i| f.push(i)))
+        .is_ok());
+    assert_eq!(Err(Error::StackOverflow), f.eval("many"));
+    assert_eq!(
+        Err(Error::DictionaryFull),
+        f.define_native("more", |_| Ok(()))
+    );
+}
+
+#[test]
+fn native_words_cannot_remove_running_code() {
+    let mut f = Forth::new();
+    let saved = f.checkpoint();
+    let mut image = vec![];
+    assert!(f.save(&mut image).is_ok());
+    assert!(f.define_native("zap", |f| f.forget("zap")).is_ok());
+    assert!(f.define_native("undo", move |f| f.restore(&saved)).is_ok());
+    assert!(f
+        .define_native("reload", move |f| f.load(image.as_slice()))
+        .is_ok());
+    assert!(f.define_native("ev", |f| f.eval("forget zap")).is_ok());
+    for word in ["zap", "undo", "reload", "ev"] {
+        assert!(f.eval(&format!(": w {word} 1 ;")).is_ok());
+        assert_eq!(Err(Error::Busy), f.eval("w"), "{word}");
+    }
+    assert!(f.forget("zap").is_ok());
+}
+
+#[test]
+fn native_eval_counts_towards_the_limit() {
+    let mut f = Forth::with_limits(ForthLimits {
+        max_instructions: 50,
+        ..Default::default()
+    });
+    assert!(f.define_native("ev", |f| f.eval(": y 1 drop ; y")).is_ok());
+    assert!(f.eval(": x begin ev 0 until ;").is_ok());
+    assert_eq!(Err(Error::InstructionLimit), f.eval("x"));
+    assert!(f.eval("y").is_ok());
+}
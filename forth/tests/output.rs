@@ -0,0 +1,95 @@
+mod common;
+
+use std::io::{self, Write};
+
+use common::{forth, Capture};
+use forth::{Error, Forth};
+
+#[test]
+fn dot_prints_and_pops() {
+    let (mut f, out) = forth();
+    assert!(f.eval("1 2 . .").is_ok());
+    assert_eq!("2 1 ", out.take());
+    assert!(f.stack().is_empty());
+    assert_eq!(Err(Error::StackUnderflow), f.eval("."));
+}
+
+#[test]
+fn emit_and_cr() {
+    let (mut f, out) = forth();
+    assert!(f.eval("72 emit 105 emit cr").is_ok());
+    assert_eq!("Hi\n", out.take());
+}
+
+#[test]
+fn dot_quote_keeps_case_and_spacing() {
+    let (mut f, out) = forth();
+    assert!(f.eval(r#"." Hello,  World!" ." x""#).is_ok());
+    assert_eq!("Hello,  World!x", out.take());
+}
+
+#[test]
+fn dot_quote_in_definition() {
+    let (mut f, out) = forth();
+    assert!(f.eval(r#": greet ." hi " . ;"#).is_ok());
+    assert!(out.take().is_empty());
+    assert!(f.eval("3 greet 4 greet").is_ok());
+    assert_eq!("hi 3 hi 4 ", out.take());
+}
+
+#[test]
+fn unterminated_dot_quote() {
+    let (mut f, _) = forth();
+    assert_eq!(Err(Error::InvalidWord), f.eval(r#"." oops"#));
+    assert_eq!(Err(Error::InvalidWord), f.eval(r#": foo ." oops ;"#));
+}
+
+#[test]
+fn dot_s_shows_the_stack() {
+    let (mut f, out) = forth();
+    assert!(f.eval(".s 1 2 3 .s").is_ok());
+    assert_eq!("<0> <3> 1 2 3 ", out.take());
+    assert_eq!(vec![1, 2, 3], f.stack());
+}
+
+#[test]
+fn output_can_be_redirected() {
+    let (mut f, first) = forth();
+    let second = Capture::default();
+    f.set_output(second.clone());
+    assert!(f.eval("1 .").is_ok());
+    assert!(first.take().is_empty());
+    assert_eq!("1 ", second.take());
+}
+
+#[test]
+fn interpreters_move_between_threads() {
+    fn send<T: Send>() {}
+    send::<Forth>();
+    let (mut f, out) = forth();
+    let worker = std::thread::spawn(move || {
+        assert!(f.eval("1 .").is_ok());
+        f
+    });
+    let mut f = worker.join().unwrap();
+    assert!(f.eval("2 .").is_ok());
+    assert_eq!("1 2 ", out.take());
+}
+
+struct Broken;
+
+impl Write for Broken {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::ErrorKind::BrokenPipe.into())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn write_errors_are_reported() {
+    let mut f = Forth::with_output(Broken);
+    assert_eq!(Err(Error::Io(io::ErrorKind::BrokenPipe)), f.eval("1 ."));
+}
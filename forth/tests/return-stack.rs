@@ -0,0 +1,121 @@
+use forth::{Error, Forth, RETURN_STACK};
+
+#[test]
+fn factorial() {
+    let mut f = Forth::new();
+    assert!(f
+        .eval(": fact dup 1 - dup if recurse * else drop then ; 1 fact 5 fact 10 fact")
+        .is_ok());
+    assert_eq!(f.stack(), [1, 120, 3628800]);
+}
+
+#[test]
+fn recurse_ignores_later_redefinitions() {
+    let mut f = Forth::new();
+    assert!(f
+        .eval(": down dup if 1 - recurse then ; : twice down down ; : down 100 ; 3 twice")
+        .is_ok());
+    assert_eq!(f.stack(), [0]);
+    assert!(f.eval("forget down 3 down").is_ok());
+    assert_eq!(f.stack(), [0, 0]);
+}
+
+#[test]
+fn fibonacci_round_trips() {
+    let mut f = Forth::new();
+    assert!(f
+        .eval(": fib dup 1 - 0 max if dup 1 - recurse swap 2 - recurse + then ; 10 fib")
+        .is_ok());
+    assert_eq!(f.stack(), [55]);
+    assert_eq!(
+        Some(": fib dup 1 - 0 max if dup 1 - recurse swap 2 - recurse + then ;".to_string()),
+        f.see("fib")
+    );
+}
+
+#[test]
+fn runaway_recursion_overflows() {
+    let mut f = Forth::new();
+    assert!(f.eval(": down dup if 1 - recurse then ;").is_ok());
+    assert!(f.eval(&format!("{} down", RETURN_STACK - 1)).is_ok());
+    assert_eq!(f.stack(), [0]);
+    assert_eq!(
+        Err(Error::ReturnStackOverflow),
+        f.eval(&format!("{} down", RETURN_STACK + 1))
+    );
+    assert!(f.eval("1 down").is_ok());
+}
+
+#[test]
+fn to_r_and_r_from() {
+    let mut f = Forth::new();
+    assert!(f.eval(": under >r 10 r> ; 1 2 under").is_ok());
+    assert_eq!(f.stack(), [1, 10, 2]);
+}
+
+#[test]
+fn r_fetch_copies() {
+    let mut f = Forth::new();
+    assert!(f.eval(": twice >r r@ r@ r> + + ; 4 twice").is_ok());
+    assert_eq!(f.stack(), [12]);
+}
+
+#[test]
+fn words_must_balance_the_return_stack() {
+    let mut f = Forth::new();
+    assert!(f.eval(": stash >r ; : get r> ; : both stash get ;").is_ok());
+    assert_eq!(Err(Error::UnbalancedReturnStack), f.eval("42 stash"));
+    assert_eq!(Err(Error::ReturnStackUnderflow), f.eval("get"));
+    assert_eq!(Err(Error::UnbalancedReturnStack), f.eval("7 both"));
+    let d = f.eval_diagnostic("7 stash").unwrap_err();
+    assert_eq!(Error::UnbalancedReturnStack, d.error);
+    assert_eq!(vec!["stash"], d.trace);
+    let d = f.eval_diagnostic("7 both").unwrap_err();
+    assert_eq!(vec!["both", "stash"], d.trace);
+}
+
+#[test]
+fn return_stack_underflow() {
+    let mut f = Forth::new();
+    assert!(f.eval(": bad r> ; : peek r@ ;").is_ok());
+    assert_eq!(Err(Error::ReturnStackUnderflow), f.eval("bad"));
+    assert_eq!(Err(Error::ReturnStackUnderflow), f.eval("peek"));
+}
+
+#[test]
+fn errors_discard_pending_values() {
+    let mut f = Forth::new();
+    assert!(f.eval(": oops >r 0 0 / ; : take r> ;").is_ok());
+    assert_eq!(Err(Error::DivisionByZero), f.eval("5 oops"));
+    assert_eq!(Err(Error::ReturnStackUnderflow), f.eval("take"));
+}
+
+#[test]
+fn return_stack_words_are_compile_only() {
+    let mut f = Forth::new();
+    for word in [">r", "r>", "r@", "recurse"] {
+        assert_eq!(Err(Error::CompileOnly), f.eval(&format!("1 {word}")));
+    }
+}
+
+#[test]
+fn bracket_char_is_compile_only() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::CompileOnly), f.eval("[char] a"));
+    assert_eq!(Err(Error::CompileOnly), f.eval("[CHAR] a"));
+    assert!(f.stack().is_empty());
+}
+
+#[test]
+fn return_stack_words_survive_images() {
+    let mut f = Forth::new();
+    assert!(f
+        .eval(": sum dup if dup >r 1 - recurse r> + then ;")
+        .is_ok());
+    let mut image = vec![];
+    assert!(f.save(&mut image).is_ok());
+    let mut g = Forth::new();
+    assert!(g.load(image.as_slice()).is_ok());
+    assert!(g.eval("4 sum").is_ok());
+    assert_eq!(g.stack(), [10]);
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::run;
+use forth::{Error, Forth, ForthLimits};
+
+#[test]
+fn rot() {
+    assert_eq!(run("1 2 3 rot"), [2, 3, 1]);
+    assert_eq!(run("0 1 2 3 -rot"), [0, 3, 1, 2]);
+    assert_eq!(run("1 2 3 rot -rot"), [1, 2, 3]);
+}
+
+#[test]
+fn nip_and_tuck() {
+    assert_eq!(run("1 2 3 nip"), [1, 3]);
+    assert_eq!(run("1 2 3 tuck"), [1, 3, 2, 3]);
+}
+
+#[test]
+fn pick() {
+    assert_eq!(run("1 2 3 0 pick"), [1, 2, 3, 3]);
+    assert_eq!(run("1 2 3 2 pick"), [1, 2, 3, 1]);
+}
+
+#[test]
+fn roll() {
+    assert_eq!(run("1 2 3 4 3 roll"), [2, 3, 4, 1]);
+    assert_eq!(run("1 2 3 2 roll"), [2, 3, 1]);
+    assert_eq!(run("1 2 3 1 roll"), [1, 3, 2]);
+    assert_eq!(run("1 2 3 0 roll"), [1, 2, 3]);
+}
+
+#[test]
+fn double_cell_words() {
+    assert_eq!(run("1 2 2dup"), [1, 2, 1, 2]);
+    assert_eq!(run("1 2 3 2drop"), [1]);
+    assert_eq!(run("1 2 3 4 2swap"), [3, 4, 1, 2]);
+    assert_eq!(run("1 2 3 4 2over"), [1, 2, 3, 4, 1, 2]);
+}
+
+#[test]
+fn depth() {
+    assert_eq!(run("depth"), [0]);
+    assert_eq!(run("5 6 depth"), [5, 6, 2]);
+}
+
+#[test]
+fn underflow() {
+    let cases = [
+        ("1 2 rot", 3),
+        ("1 2 -rot", 3),
+        ("1 nip", 2),
+        ("1 tuck", 2),
+        ("1 2dup", 2),
+        ("1 2drop", 2),
+        ("1 2 3 2swap", 4),
+        ("1 2 3 2over", 4),
+    ];
+    for (input, needed) in cases {
+        let mut f = Forth::new();
+        assert_eq!(Err(Error::StackUnderflow), f.eval(input), "{input}");
+        assert_eq!(f.stack().len(), needed - 1, "{input} changed the stack");
+    }
+}
+
+#[test]
+fn pick_and_roll_check_their_index() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::StackUnderflow), f.eval("pick"));
+    assert_eq!(Err(Error::StackUnderflow), f.eval("1 2 2 pick"));
+    assert_eq!(Err(Error::StackUnderflow), f.eval("1 2 -1 roll"));
+    assert_eq!(Err(Error::StackUnderflow), Forth::new().eval("roll"));
+}
+
+#[test]
+fn case_insensitive() {
+    assert_eq!(run("1 2 3 ROT 2DUP Depth"), [2, 3, 1, 3, 1, 5]);
+}
+
+#[test]
+fn can_be_redefined() {
+    assert_eq!(run(": nip drop drop ; 1 2 3 nip"), [1]);
+}
+
+#[test]
+fn compiled_into_definitions() {
+    let mut f = Forth::new();
+    assert!(f.eval(": 3dup 2 pick 2 pick 2 pick ; 1 2 3 3dup").is_ok());
+    assert_eq!(f.stack(), [1, 2, 3, 1, 2, 3]);
+    assert_eq!(
+        Some(": 3dup 2 pick 2 pick 2 pick ;".to_string()),
+        f.see("3dup")
+    );
+}
+
+#[test]
+fn growing_words_respect_the_stack_limit() {
+    let mut f = Forth::with_limits(ForthLimits {
+        max_stack_depth: 3,
+        ..Default::default()
+    });
+    assert_eq!(Err(Error::StackOverflow), f.eval("1 2 2dup"));
+    assert_eq!(Err(Error::StackOverflow), f.eval("depth tuck"));
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use common::forth;
+use forth::{Error, Forth, Value, DATA_SPACE};
+
+#[test]
+fn paren_comments_are_skipped() {
+    let mut f = Forth::new();
+    assert!(f.eval("1 ( 2 3 ) 4 : sq ( n -- n*n ) dup * ; 5 sq").is_ok());
+    assert_eq!(f.stack(), [1, 4, 25]);
+    assert_eq!(Some(": sq dup * ;".to_string()), f.see("sq"));
+}
+
+#[test]
+fn paren_comments_span_lines() {
+    let mut f = Forth::new();
+    assert!(f.eval("1 ( a\nlonger comment ) 2 ( unterminated 3").is_ok());
+    assert_eq!(f.stack(), [1, 2]);
+}
+
+#[test]
+fn line_comments_end_at_newline() {
+    let mut f = Forth::new();
+    assert!(f.eval("1 \\ 2 3\n4 \\ 5").is_ok());
+    assert_eq!(f.stack(), [1, 4]);
+}
+
+#[test]
+fn comment_words_need_whitespace() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::UnknownWord), f.eval("(1)"));
+}
+
+#[test]
+fn s_quote_keeps_case() {
+    let (mut f, out) = forth();
+    assert!(f.eval(r#"S" Hello, World" type"#).is_ok());
+    assert_eq!("Hello, World", out.take());
+    assert!(f.stack().is_empty());
+}
+
+#[test]
+fn s_quote_in_definitions() {
+    let (mut f, out) = forth();
+    assert!(f.eval(r#": greet s" Hi " type ; greet greet"#).is_ok());
+    assert_eq!("Hi Hi ", out.take());
+    assert_eq!(
+        Some(r#": greet s" Hi " type ;"#.to_string()),
+        f.see("greet")
+    );
+}
+
+#[test]
+fn s_quote_pushes_address_and_length() {
+    let mut f = Forth::new();
+    assert!(f.eval(r#"variable x s" abc" x @"#).is_ok());
+    assert_eq!(f.stack(), [DATA_SPACE as Value, 3, 0]);
+    assert!(f.eval("here").is_ok());
+    assert_eq!(f.stack(), [DATA_SPACE as Value, 3, 0, 1]);
+}
+
+#[test]
+fn interpreted_strings_leave_the_data_space_alone() {
+    let mut f = Forth::new();
+    for _ in 0..DATA_SPACE / 4 + 1 {
+        assert!(f.eval(r#"s" text" 2drop"#).is_ok());
+    }
+    assert!(f.eval(r#"here s" text" drop @ variable x x"#).is_ok());
+    assert_eq!(f.stack(), [0, 't' as Value, 0]);
+}
+
+#[test]
+fn interpreted_strings_reuse_two_buffers() {
+    let (mut f, out) = forth();
+    assert!(f.eval(r#"s" one" s" two" type type"#).is_ok());
+    assert_eq!("twoone", out.take());
+    // the third string overwrites the first
+    assert!(f.eval(r#"s" abc" s" de" s" f" type type"#).is_ok());
+    assert_eq!("fde", out.take());
+    assert_eq!(Err(Error::InvalidAddress), f.eval("type"));
+}
+
+#[test]
+fn count_reads_counted_strings() {
+    let (mut f, out) = forth();
+    assert!(f.eval("create msg 2 , 104 , 105 , msg count").is_ok());
+    assert_eq!(f.stack(), [1, 2]);
+    assert!(f.eval("type").is_ok());
+    assert_eq!("hi", out.take());
+}
+
+#[test]
+fn type_checks_addresses() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::InvalidAddress), f.eval("0 1 type"));
+    assert_eq!(Err(Error::InvalidAddress), f.eval(r#"s" ab" 1 + type"#));
+    assert_eq!(Err(Error::InvalidAddress), f.eval("0 -1 type"));
+    assert_eq!(Err(Error::StackUnderflow), f.eval("count"));
+}
+
+#[test]
+fn char_pushes_the_first_character() {
+    let mut f = Forth::new();
+    assert!(f.eval("char A char abc : z [char] Z [CHAR] z ; z").is_ok());
+    assert_eq!(f.stack(), [65, 97, 90, 122]);
+    assert_eq!(Err(Error::InvalidWord), f.eval("char"));
+    assert_eq!(Err(Error::InvalidWord), f.eval(": y [char] ;"));
+}
+
+#[test]
+fn words_stay_case_insensitive() {
+    let (mut f, out) = forth();
+    assert!(f.eval(r#": Shout S" LOUD" TYPE ; SHOUT shout"#).is_ok());
+    assert_eq!("LOUDLOUD", out.take());
+}
+
+#[test]
+fn failed_definitions_release_their_strings() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::InvalidWord), f.eval(r#": bad s" text" nope ;"#));
+    assert!(f.eval("here").is_ok());
+    assert_eq!(f.stack(), [0]);
+    assert!(f.eval(r#": ok s" text" ; forget ok here"#).is_ok());
+    assert_eq!(f.stack(), [0, 0]);
+}
+
+#[test]
+fn incomplete_strings_and_comments() {
+    assert!(Forth::is_incomplete(r#"s" open"#));
+    assert!(Forth::is_incomplete("1 ( open"));
+    assert!(!Forth::is_incomplete("1 ( closed ) 2"));
+    assert!(!Forth::is_incomplete(": foo \\ ; in a comment\n;"));
+    assert!(Forth::is_incomplete(": foo ( ; ) 1"));
+}
@@ -0,0 +1,113 @@
+use std::sync::{Arc, Mutex};
+
+use forth::{Error, Forth, Value};
+
+type Log = Arc<Mutex<Vec<(String, String, Vec<Value>, usize)>>>;
+
+fn traced(f: &mut Forth) -> Log {
+    let log = Log::default();
+    let events = Arc::clone(&log);
+    f.set_trace(move |e| {
+        events.lock().unwrap().push((
+            e.word.to_string(),
+            e.instruction.clone(),
+            e.stack.to_vec(),
+            e.depth,
+        ));
+        true
+    });
+    log
+}
+
+fn event(
+    word: &str,
+    instruction: &str,
+    stack: &[Value],
+    depth: usize,
+) -> (String, String, Vec<Value>, usize) {
+    (
+        word.to_string(),
+        instruction.to_string(),
+        stack.to_vec(),
+        depth,
+    )
+}
+
+#[test]
+fn hook_sees_every_instruction_before_it_runs() {
+    let mut f = Forth::new();
+    assert!(f.eval(": sq dup * ; : quad sq sq ;").is_ok());
+    let log = traced(&mut f);
+    assert!(f.eval("2 quad").is_ok());
+    assert_eq!(
+        *log.lock().unwrap(),
+        [
+            event("quad", "sq", &[2], 1),
+            event("sq", "dup", &[2], 2),
+            event("sq", "*", &[2, 2], 2),
+            event("sq", ";", &[4], 2),
+            event("quad", "sq", &[4], 1),
+            event("sq", "dup", &[4], 2),
+            event("sq", "*", &[4, 4], 2),
+            event("sq", ";", &[16], 2),
+            event("quad", ";", &[16], 1),
+        ]
+    );
+}
+
+#[test]
+fn control_words_are_spelled_as_in_the_source() {
+    let mut f = Forth::new();
+    assert!(f.eval(": pos 0 > if 1 else 0 then ;").is_ok());
+    let log = traced(&mut f);
+    assert!(f.eval("5 pos").is_ok());
+    let instructions: Vec<_> = log.lock().unwrap().iter().map(|e| e.1.clone()).collect();
+    assert_eq!(instructions, ["0", ">", "if", "1", "else", ";"]);
+}
+
+#[test]
+fn interpreted_words_are_not_traced() {
+    let mut f = Forth::new();
+    let log = traced(&mut f);
+    assert!(f.eval("1 2 + dup").is_ok());
+    assert!(log.lock().unwrap().is_empty());
+}
+
+#[test]
+fn hook_can_interrupt() {
+    let mut f = Forth::new();
+    assert!(f.eval(": forever begin 0 until ; : run forever ;").is_ok());
+    let mut steps = 0;
+    f.set_trace(move |_| {
+        steps += 1;
+        steps <= 100
+    });
+    let err = f.eval_diagnostic("run").unwrap_err();
+    assert_eq!(Error::Interrupted, err.error);
+    assert_eq!(err.trace, ["run", "forever"]);
+}
+
+#[test]
+fn clear_trace_removes_the_hook() {
+    let mut f = Forth::new();
+    assert!(f.eval(": one 1 ;").is_ok());
+    let log = traced(&mut f);
+    f.clear_trace();
+    assert!(f.eval("one").is_ok());
+    assert!(log.lock().unwrap().is_empty());
+}
+
+#[test]
+fn events_display_as_a_trace_line() {
+    let mut f = Forth::new();
+    assert!(f.eval(": inner 7 ; : outer 1 inner ;").is_ok());
+    let lines = Arc::new(Mutex::new(vec![]));
+    let out = Arc::clone(&lines);
+    f.set_trace(move |e| {
+        out.lock().unwrap().push(e.to_string());
+        true
+    });
+    assert!(f.eval("outer").is_ok());
+    assert_eq!(lines.lock().unwrap()[2], "  inner 0: 7            <1> 1");
+    assert_eq!(lines.lock().unwrap()[0], "outer 2: 1            <0>");
+}
@@ -1,26 +1,26 @@
 use std::{
-    collections::{HashMap, HashSet},
-    sync::atomic::{AtomicU64, Ordering},
+    collections::{BTreeSet, HashMap, HashSet},
+    sync::atomic::{AtomicU32, Ordering},
 };
 
-fn input_id() -> InputCellId {
-    static ID: AtomicU64 = AtomicU64::new(0);
-    InputCellId(ID.fetch_add(1, Ordering::Relaxed))
-}
+mod slab;
+mod sync;
 
-fn compute_id() -> ComputeCellId {
-    static ID: AtomicU64 = AtomicU64::new(0);
-    ComputeCellId(ID.fetch_add(1, Ordering::Relaxed))
-}
+use slab::{Key, Slab};
+pub use sync::SyncReactor;
 
-fn callback_id() -> CallbackId {
-    static ID: AtomicU64 = AtomicU64::new(0);
-    CallbackId(ID.fetch_add(1, Ordering::Relaxed))
+// brands the ids of each reactor, so they can't be used with another one
+fn brand() -> u32 {
+    static BRAND: AtomicU32 = AtomicU32::new(0);
+    BRAND.fetch_add(1, Ordering::Relaxed)
 }
 
 /// `InputCellId` is a unique identifier for an input cell.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub struct InputCellId(u64);
+///
+/// Ids belong to the reactor that created them: other reactors treat them as nonexistent, and
+/// so does this one once the cell is removed, even if its slot has been reused since.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct InputCellId(Key);
 /// `ComputeCellId` is a unique identifier for a compute cell.
 /// Values of type `InputCellId` and `ComputeCellId` should not be mutually assignable,
 /// demonstrated by the following tests:
@@ -35,25 +35,17 @@ pub struct InputCellId(u64);
 /// let input = r.create_input(111);
 /// let compute: react::InputCellId = r.create_compute(&[react::CellId::Input(input)], |_| 222).unwrap();
 /// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ComputeCellId(Key);
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub struct ComputeCellId(u64);
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub struct CallbackId(u64);
+pub struct CallbackId(Key);
 
 struct ComputeCell<'a, T> {
     value: T,
     deps: Vec<CellId>,
-    f: Box<dyn Fn(&[T]) -> T + 'a>,
-    cbs: HashSet<CallbackId>,//  Box<dyn FnMut(T) + 'a>>,
-}
-
-impl<'a, T: Copy + PartialEq> ComputeCell<'a, T> {
-
-    fn compute(&self, args: &[T]) -> T {
-        (self.f)(args)
-    }
-
-
+    f: Compute<'a, T>,
+    // longest path from an input, so dependencies always come first
+    height: usize,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -68,60 +60,79 @@ pub enum RemoveCallbackError {
     NonexistentCallback,
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub enum RemoveCellError {
+    NonexistentCell,
+    /// The cell is still used by these compute cells, in id order.
+    HasDependents(Vec<ComputeCellId>),
+}
+
 pub enum ComputeError {
     InvalidDependency(CellId),
     NonexistentComputeId(ComputeCellId),
 }
 
+type Compute<'a, T> = Box<dyn Fn(&[&T]) -> T + 'a>;
+type Callback<'a, T> = Box<dyn FnMut(&T, &T) + 'a>;
+
 pub struct Reactor<'a, T> {
-    // Just so that the compiler doesn't complain about an unused type parameter.
-    // You probably want to delete this field.
-    inputs: HashMap<InputCellId, T>,
-    computes: HashMap<ComputeCellId, ComputeCell<'a, T>>,
+    inputs: Slab<T>,
+    computes: Slab<ComputeCell<'a, T>>,
+    // the compute cells depending directly on each cell
     listeners: HashMap<CellId, HashSet<ComputeCellId>>,
-    callbacks: HashMap<CallbackId, Box<dyn FnMut(T) + 'a>>,
+    // the callbacks of each cell, in the order they were added
+    subscribers: HashMap<CellId, Vec<CallbackId>>,
+    callbacks: Slab<Callback<'a, T>>,
 }
 
-// You are guaranteed that Reactor will only be tested against types that are Copy + PartialEq.
-impl<'a, T: Copy + PartialEq> Reactor<'a, T> {
+// Values are cloned only to hand them out: compute functions see their dependencies by reference.
+// Wrap values that are expensive to clone in an `Rc` or `Arc`.
+impl<'a, T: Clone + PartialEq> Reactor<'a, T> {
     pub fn new() -> Self {
+        let brand = brand();
         Self {
-            inputs: HashMap::new(),
-            computes: HashMap::new(),
+            inputs: Slab::new(brand),
+            computes: Slab::new(brand),
             listeners: HashMap::new(),
-            callbacks: HashMap::new(),
+            subscribers: HashMap::new(),
+            callbacks: Slab::new(brand),
         }
     }
 
     // Creates an input cell with the specified initial value, returning its ID.
     pub fn create_input(&mut self, initial: T) -> InputCellId {
-        let input_id = input_id();
-        self.inputs.insert(
-            input_id,
-            initial,
-        );
-        input_id
+        InputCellId(self.inputs.insert(initial))
     }
 
     fn check(&self, deps: &[CellId]) -> Result<(), CellId> {
         for id in deps {
-            match id {
-                r @ CellId::Input(id) => { if !self.inputs.contains_key(id) {return Err(*r)}},
-                r @ CellId::Compute(id) => { if !self.computes.contains_key(id) {return Err(*r)}},
+            let exists = match id {
+                CellId::Input(id) => self.inputs.contains(id.0),
+                CellId::Compute(id) => self.computes.contains(id.0),
+            };
+            if !exists {
+                return Err(*id);
             }
         }
         Ok(())
     }
 
-    fn args(&self, deps: &[CellId]) -> Vec<T> {
+    fn args(&self, deps: &[CellId]) -> Vec<&T> {
         deps.iter()
             .map(|id| match id {
-                CellId::Input(id) => self.inputs[id],
-                CellId::Compute(id) => self.computes[id].value,
+                CellId::Input(id) => &self.inputs[id.0],
+                CellId::Compute(id) => &self.computes[id.0].value,
             })
             .collect()
     }
 
+    fn height(&self, id: CellId) -> usize {
+        match id {
+            CellId::Input(_) => 0,
+            CellId::Compute(id) => self.computes[id.0].height,
+        }
+    }
+
     // Creates a compute cell with the specified dependencies and compute function.
     // The compute function is expected to take in its arguments in the same order as specified in
     // `dependencies`.
@@ -132,10 +143,9 @@ impl<'a, T: Copy + PartialEq> Reactor<'a, T> {
     // (If multiple dependencies do not exist, exactly which one is returned is not defined and
     // will not be tested)
     //
-    // Notice that there is no way to *remove* a cell.
-    // This means that you may assume, without checking, that if the dependencies exist at creation
-    // time they will continue to exist as long as the Reactor exists.
-    pub fn create_compute<F: Fn(&[T]) -> T + 'a>(
+    // A cell can't be removed while compute cells depend on it (see `remove_cell`), so the
+    // dependencies exist as long as this cell does.
+    pub fn create_compute<F: Fn(&[&T]) -> T + 'a>(
         &mut self,
         dependencies: &[CellId],
         compute_func: F,
@@ -144,19 +154,21 @@ impl<'a, T: Copy + PartialEq> Reactor<'a, T> {
         self.check(dependencies)?;
 
         // create compute cell
-        let compute_id = compute_id();
         let compute_cell = ComputeCell {
             value: compute_func(&self.args(dependencies)),
             f: Box::new(compute_func),
             deps: dependencies.to_owned(),
-            cbs: HashSet::new(),
+            height: 1 + dependencies
+                .iter()
+                .map(|id| self.height(*id))
+                .max()
+                .unwrap_or(0),
         };
-        self.computes.insert(compute_id, compute_cell);
+        let compute_id = ComputeCellId(self.computes.insert(compute_cell));
 
         // listeners
         for id in dependencies {
-            let e = self.listeners.entry(*id).or_insert_with(HashSet::new);
-            e.insert(compute_id);
+            self.listeners.entry(*id).or_default().insert(compute_id);
         }
         Ok(compute_id)
     }
@@ -169,9 +181,14 @@ impl<'a, T: Copy + PartialEq> Reactor<'a, T> {
     // It turns out this introduces a significant amount of extra complexity to this exercise.
     // We chose not to cover this here, since this exercise is probably enough work as-is.
     pub fn value(&self, id: CellId) -> Option<T> {
+        self.value_ref(id).cloned()
+    }
+
+    // Like `value`, but borrows the value instead of cloning it.
+    pub fn value_ref(&self, id: CellId) -> Option<&T> {
         match id {
-            CellId::Input(id) => self.inputs.get(&id).map(|&i| i),
-            CellId::Compute(id) => self.computes.get(&id).map(|c| c.value),
+            CellId::Input(id) => self.inputs.get(id.0),
+            CellId::Compute(id) => self.computes.get(id.0).map(|c| &c.value),
         }
     }
 
@@ -184,84 +201,140 @@ impl<'a, T: Copy + PartialEq> Reactor<'a, T> {
     //
     // As before, that turned out to add too much extra complexity.
     pub fn set_value(&mut self, id: InputCellId, new_value: T) -> bool {
-        if let None = self.inputs.get(&id) {
-            return false;
-        }
-        let origin = self.inputs.get_mut(&id).unwrap();
-        if *origin == new_value {
-            return true;
+        self.batch(|tx| tx.set(id, new_value))
+    }
+
+    // Sets several input cells at once.
+    //
+    // The changes made through the transaction are propagated together once `f` returns, so each
+    // callback is called at most once, with the final value of its cell.
+    pub fn batch<R>(&mut self, f: impl FnOnce(&mut Transaction<'_, 'a, T>) -> R) -> R {
+        let mut tx = Transaction {
+            reactor: self,
+            changed: vec![],
+        };
+        let result = f(&mut tx);
+        let changed = tx.changed;
+        self.propagate(changed);
+        result
+    }
+
+    // Recomputes every compute cell downstream of the `changed` inputs exactly once, dependencies
+    // first, then calls the callbacks of the cells whose value changed.
+    fn propagate(&mut self, changed: Vec<(InputCellId, T)>) {
+        let mut updated = vec![];
+        // ordered by height, so a cell is only recomputed once all its dependencies are
+        let mut queue = BTreeSet::new();
+        for (id, old) in changed {
+            if self.inputs[id.0] != old {
+                self.enqueue(CellId::Input(id), &mut queue);
+                updated.push((CellId::Input(id), old));
+            }
         }
-        *origin = new_value;
-        // propagate update
-        if let None = self.listeners.get(&CellId::Input(id)) {
-            return true;
+
+        while let Some((_, id)) = queue.pop_first() {
+            let cell = &self.computes[id.0];
+            let value = (cell.f)(&self.args(&cell.deps));
+            let cell = &mut self.computes[id.0];
+            if value != cell.value {
+                let old = std::mem::replace(&mut cell.value, value);
+                updated.push((CellId::Compute(id), old));
+                self.enqueue(CellId::Compute(id), &mut queue);
+            }
         }
-        let listeners = self.listeners.get(&CellId::Input(id)).unwrap();
-        for id in listeners.clone() {
-            let c: &ComputeCell<_> = self.computes.get(&id).unwrap();
-            let cbs = c.cbs.clone();
-            let origin = c.value;
-
-            let v = self.compute(id, &mut HashMap::new());
-            if Some(origin) != v {
-                cbs.iter().for_each(|cb| self.callbacks.get_mut(&cb).unwrap()(v.unwrap()));
+
+        for (id, old) in updated {
+            let new = match id {
+                CellId::Input(id) => &self.inputs[id.0],
+                CellId::Compute(id) => &self.computes[id.0].value,
+            };
+            for cb in self.subscribers.get(&id).into_iter().flatten() {
+                (self.callbacks[cb.0])(&old, new);
             }
         }
-        true
     }
 
-    fn dep_value(&self, id: ComputeCellId) -> Vec<T> {
-        let cell = self.computes.get(&id).unwrap();
-        cell.deps
-            .iter()
-            .map(|id| self.value(*id).unwrap())
-            .collect::<Vec<_>>()
+    fn enqueue(&self, id: CellId, queue: &mut BTreeSet<(usize, ComputeCellId)>) {
+        for &dependent in self.listeners.get(&id).into_iter().flatten() {
+            queue.insert((self.computes[dependent.0].height, dependent));
+        }
     }
 
-    pub fn compute(&mut self, id: ComputeCellId, callbacks: &mut HashMap<CallbackId, Box<dyn FnMut(T) + 'a>>) -> Option<T> {
-        if let None = self.computes.get(&id) {
-            return None;
+    // Removes the specified cell.
+    //
+    // Returns an Err if the cell doesn't exist, or if compute cells still depend on it, listing
+    // them. Use `remove_cell_cascade` to remove those as well.
+    pub fn remove_cell(&mut self, id: CellId) -> Result<(), RemoveCellError> {
+        self.check(&[id])
+            .map_err(|_| RemoveCellError::NonexistentCell)?;
+        if let Some(dependents) = self.listeners.get(&id).filter(|l| !l.is_empty()) {
+            let mut dependents: Vec<_> = dependents.iter().copied().collect();
+            dependents.sort();
+            return Err(RemoveCellError::HasDependents(dependents));
         }
-        let args = self.dep_value(id);
-        let c: &mut ComputeCell<T> = self.computes.get_mut(&id).unwrap();
-        let origin = c.value;
-        let value = c.compute(&args);
-        let cbs = c.cbs.clone();
-
-        c.value = value;
-        if value != origin {
-            let listeners = self.listeners.get(&CellId::Compute(id));
-            if let Some(sets) = listeners {
-                sets.clone().iter().for_each(|id| {self.compute(*id, callbacks);});
+        self.remove(id);
+        Ok(())
+    }
+
+    // Removes the specified cell along with every compute cell depending on it, directly or
+    // indirectly, and their callbacks.
+    //
+    // Returns an Err if the cell doesn't exist.
+    pub fn remove_cell_cascade(&mut self, id: CellId) -> Result<(), RemoveCellError> {
+        self.check(&[id])
+            .map_err(|_| RemoveCellError::NonexistentCell)?;
+        let dependents = self.listeners.get(&id).cloned().unwrap_or_default();
+        for dependent in dependents {
+            // already gone if it also depended on an earlier dependent
+            if self.computes.contains(dependent.0) {
+                self.remove_cell_cascade(CellId::Compute(dependent))?;
             }
+        }
+        self.remove(id);
+        Ok(())
+    }
 
-            // callbacks
-            cbs.iter().for_each(|cb| self.callbacks.get_mut(&cb).unwrap()(value));
+    // removes a cell nothing depends on
+    fn remove(&mut self, id: CellId) {
+        self.listeners.remove(&id);
+        for cb in self.subscribers.remove(&id).into_iter().flatten() {
+            self.callbacks.remove(cb.0);
+        }
+        let compute = match id {
+            CellId::Input(input) => {
+                self.inputs.remove(input.0);
+                return;
+            }
+            CellId::Compute(compute) => compute,
+        };
+        let cell = self.computes.remove(compute.0).unwrap();
+        for dep in &cell.deps {
+            if let Some(listeners) = self.listeners.get_mut(dep) {
+                listeners.remove(&compute);
+            }
         }
-        Some(value)
     }
 
-    // Adds a callback to the specified compute cell.
+    // Adds a callback to the specified cell.
     //
     // Returns the ID of the just-added callback, or None if the cell doesn't exist.
     //
-    // Callbacks on input cells will not be tested.
-    //
     // The semantics of callbacks (as will be tested):
-    // For a single set_value call, each compute cell's callbacks should each be called:
-    // * Zero times if the compute cell's value did not change as a result of the set_value call.
-    // * Exactly once if the compute cell's value changed as a result of the set_value call.
-    //   The value passed to the callback should be the final value of the compute cell after the
-    //   set_value call.
-    pub fn add_callback<F: FnMut(T) + 'a>(
+    // For a single set_value call, each cell's callbacks should each be called:
+    // * Zero times if the cell's value did not change as a result of the set_value call.
+    // * Exactly once if the cell's value changed as a result of the set_value call.
+    //   The values passed to the callback should be the value of the cell before the set_value
+    //   call, and its final value after it.
+    //
+    // Callbacks run once every affected cell has been recomputed, in dependency order.
+    pub fn add_callback<F: FnMut(&T, &T) + 'a>(
         &mut self,
-        id: ComputeCellId,
+        id: CellId,
         callback: F,
     ) -> Option<CallbackId> {
-        let cell = self.computes.get_mut(&id)?;
-        let cid = callback_id();
-        cell.cbs.insert(cid);
-        self.callbacks.insert(cid, Box::new(callback));
+        self.check(&[id]).ok()?;
+        let cid = CallbackId(self.callbacks.insert(Box::new(callback)));
+        self.subscribers.entry(id).or_default().push(cid);
         Some(cid)
     }
 
@@ -272,28 +345,54 @@ impl<'a, T: Copy + PartialEq> Reactor<'a, T> {
     // A removed callback should no longer be called.
     pub fn remove_callback(
         &mut self,
-        cell: ComputeCellId,
+        cell: CellId,
         callback: CallbackId,
     ) -> Result<(), RemoveCallbackError> {
-        if let Some(c) = self.computes.get_mut(&cell) {
-            if c.cbs.remove(&callback) {
-                return Ok(());
-            }
-            return Err(RemoveCallbackError::NonexistentCallback);
-        }
-        Err(RemoveCallbackError::NonexistentCell)
+        self.check(&[cell])
+            .map_err(|_| RemoveCallbackError::NonexistentCell)?;
+        let cbs = self
+            .subscribers
+            .get_mut(&cell)
+            .ok_or(RemoveCallbackError::NonexistentCallback)?;
+        let i = cbs
+            .iter()
+            .position(|&cb| cb == callback)
+            .ok_or(RemoveCallbackError::NonexistentCallback)?;
+        cbs.remove(i);
+        self.callbacks.remove(callback.0);
+        Ok(())
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// A set of input changes, propagated together by `Reactor::batch`.
+pub struct Transaction<'r, 'a, T> {
+    reactor: &'r mut Reactor<'a, T>,
+    // the inputs set so far, with their values from before the batch
+    changed: Vec<(InputCellId, T)>,
+}
+
+impl<T: Clone + PartialEq> Transaction<'_, '_, T> {
+    // Sets the value of the specified input cell, without propagating it yet.
+    //
+    // Returns false if the cell does not exist.
+    pub fn set(&mut self, id: InputCellId, new_value: T) -> bool {
+        let Some(value) = self.reactor.inputs.get_mut(id.0) else {
+            return false;
+        };
+        if *value != new_value {
+            let old = std::mem::replace(value, new_value);
+            if !self.changed.iter().any(|(changed, _)| *changed == id) {
+                self.changed.push((id, old));
+            }
+        }
+        true
+    }
 
-    #[test]
-    fn id() {
-        let cid = callback_id();
-        assert_eq!(cid.0, 0);
-        let cid = callback_id();
-        assert_eq!(cid.0, 1);
+    // Retrieves the value of the cell, or None if the cell does not exist.
+    //
+    // Input cells already hold the values set in this transaction, while compute cells keep
+    // their old values until the batch ends.
+    pub fn value(&self, id: CellId) -> Option<T> {
+        self.reactor.value(id)
     }
 }
@@ -0,0 +1,123 @@
+use std::ops::{Index, IndexMut};
+
+/// Identifies a value in a `Slab`: the brand of the owning reactor, the slot, and how many
+/// times that slot had been reused, so that ids of removed values never alias newer ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Key {
+    brand: u32,
+    index: u32,
+    generation: u32,
+}
+
+struct Slot<V> {
+    generation: u32,
+    value: Option<V>,
+}
+
+/// Values stored in reusable slots.
+pub struct Slab<V> {
+    brand: u32,
+    slots: Vec<Slot<V>>,
+    free: Vec<u32>,
+}
+
+impl<V> Slab<V> {
+    pub fn new(brand: u32) -> Self {
+        Self {
+            brand,
+            slots: vec![],
+            free: vec![],
+        }
+    }
+
+    pub fn insert(&mut self, value: V) -> Key {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    value: None,
+                });
+                (self.slots.len() - 1) as u32
+            }
+        };
+        let slot = &mut self.slots[index as usize];
+        slot.value = Some(value);
+        Key {
+            brand: self.brand,
+            index,
+            generation: slot.generation,
+        }
+    }
+
+    fn slot(&self, key: Key) -> Option<&Slot<V>> {
+        let slot = self.slots.get(key.index as usize)?;
+        (key.brand == self.brand && key.generation == slot.generation).then_some(slot)
+    }
+
+    pub fn contains(&self, key: Key) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn get(&self, key: Key) -> Option<&V> {
+        self.slot(key)?.value.as_ref()
+    }
+
+    pub fn get_mut(&mut self, key: Key) -> Option<&mut V> {
+        self.slot(key)?;
+        self.slots[key.index as usize].value.as_mut()
+    }
+
+    pub fn remove(&mut self, key: Key) -> Option<V> {
+        self.slot(key)?;
+        let slot = &mut self.slots[key.index as usize];
+        let value = slot.value.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(key.index);
+        Some(value)
+    }
+}
+
+impl<V> Index<Key> for Slab<V> {
+    type Output = V;
+
+    fn index(&self, key: Key) -> &V {
+        self.get(key).expect("no value for key")
+    }
+}
+
+impl<V> IndexMut<Key> for Slab<V> {
+    fn index_mut(&mut self, key: Key) -> &mut V {
+        self.get_mut(key).expect("no value for key")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slots_are_reused() {
+        let mut slab = Slab::new(0);
+        let a = slab.insert('a');
+        let b = slab.insert('b');
+        assert_eq!((a.index, b.index), (0, 1));
+        assert_eq!(slab.remove(a), Some('a'));
+        let c = slab.insert('c');
+        assert_eq!(c.index, 0);
+        assert_eq!(slab.get(a), None);
+        assert_eq!(slab.remove(a), None);
+        assert_eq!(slab[c], 'c');
+    }
+
+    #[test]
+    fn keys_are_branded() {
+        let mut one = Slab::new(1);
+        let mut two = Slab::new(2);
+        let a = one.insert('a');
+        let _ = two.insert('b');
+        assert_eq!(two.get(a), None);
+        assert_eq!(two.remove(a), None);
+        assert_eq!(one.get(a), Some(&'a'));
+    }
+}
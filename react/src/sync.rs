@@ -0,0 +1,143 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use crate::{
+    CallbackId, CellId, ComputeCellId, InputCellId, Reactor, RemoveCallbackError, RemoveCellError,
+    Transaction,
+};
+
+type Job<T> = Box<dyn FnOnce(&mut Reactor<'static, T>) + Send>;
+
+/// A `Reactor` that can be shared between threads.
+///
+/// The cells live on a thread of their own, which runs the operations one at a time, so
+/// propagation is serialized and callbacks always run on that thread. Compute functions and
+/// callbacks must not use the `SyncReactor` themselves, as that would wait on the very thread
+/// running them. If one panics, the panic is resumed in the caller whose operation ran it and
+/// the thread goes on serving the others, though cells downstream of that change may be left
+/// with stale values until their inputs change again.
+/// Clones share the same cells; the thread stops once every clone has been dropped.
+pub struct SyncReactor<T> {
+    jobs: Sender<Job<T>>,
+}
+
+impl<T> Clone for SyncReactor<T> {
+    fn clone(&self) -> Self {
+        Self {
+            jobs: self.jobs.clone(),
+        }
+    }
+}
+
+impl<T: Clone + PartialEq + Send + 'static> SyncReactor<T> {
+    pub fn new() -> Self {
+        let (jobs, queue) = mpsc::channel::<Job<T>>();
+        thread::Builder::new()
+            .name("reactor".to_string())
+            .spawn(move || {
+                let mut reactor = Reactor::new();
+                for job in queue {
+                    job(&mut reactor);
+                }
+            })
+            .expect("failed to spawn the reactor thread");
+        Self { jobs }
+    }
+
+    // runs `f` on the reactor thread and waits for its result, resuming its panic if it panics
+    fn run<R: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut Reactor<'static, T>) -> R + Send + 'static,
+    ) -> R {
+        let (reply, result) = mpsc::channel();
+        let job: Job<T> = Box::new(move |reactor| {
+            let _ = reply.send(panic::catch_unwind(AssertUnwindSafe(|| f(reactor))));
+        });
+        let result = self
+            .jobs
+            .send(job)
+            .ok()
+            .and_then(|()| result.recv().ok())
+            .expect("the reactor thread stopped");
+        result.unwrap_or_else(|payload| panic::resume_unwind(payload))
+    }
+
+    // See `Reactor::create_input`.
+    pub fn create_input(&self, initial: T) -> InputCellId {
+        self.run(move |r| r.create_input(initial))
+    }
+
+    // See `Reactor::create_compute`. The compute function runs on the reactor thread.
+    pub fn create_compute<F: Fn(&[&T]) -> T + Send + 'static>(
+        &self,
+        dependencies: &[CellId],
+        compute_func: F,
+    ) -> Result<ComputeCellId, CellId> {
+        let dependencies = dependencies.to_owned();
+        self.run(move |r| r.create_compute(&dependencies, compute_func))
+    }
+
+    // See `Reactor::value`.
+    pub fn value(&self, id: CellId) -> Option<T> {
+        self.run(move |r| r.value(id))
+    }
+
+    // See `Reactor::set_value`. Returns once the change has propagated and the callbacks have
+    // run.
+    pub fn set_value(&self, id: InputCellId, new_value: T) -> bool {
+        self.run(move |r| r.set_value(id, new_value))
+    }
+
+    // See `Reactor::batch`. No other operation runs until `f` returns.
+    pub fn batch<R: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut Transaction<'_, 'static, T>) -> R + Send + 'static,
+    ) -> R {
+        self.run(move |r| r.batch(f))
+    }
+
+    // See `Reactor::remove_cell`.
+    pub fn remove_cell(&self, id: CellId) -> Result<(), RemoveCellError> {
+        self.run(move |r| r.remove_cell(id))
+    }
+
+    // See `Reactor::remove_cell_cascade`.
+    pub fn remove_cell_cascade(&self, id: CellId) -> Result<(), RemoveCellError> {
+        self.run(move |r| r.remove_cell_cascade(id))
+    }
+
+    // See `Reactor::add_callback`. The callback runs on the reactor thread.
+    pub fn add_callback<F: FnMut(&T, &T) + Send + 'static>(
+        &self,
+        id: CellId,
+        callback: F,
+    ) -> Option<CallbackId> {
+        self.run(move |r| r.add_callback(id, callback))
+    }
+
+    // Subscribes to the changes of the specified cell, returning the ID of the underlying
+    // callback and a channel receiving the old and new value of each change, or None if the cell
+    // doesn't exist.
+    //
+    // The callback stays registered when the receiver is dropped; remove it with
+    // `remove_callback`.
+    pub fn subscribe(&self, id: CellId) -> Option<(CallbackId, Receiver<(T, T)>)> {
+        let (changes, receiver) = mpsc::channel();
+        let callback = self.add_callback(id, move |old: &T, new: &T| {
+            let _ = changes.send((old.clone(), new.clone()));
+        })?;
+        Some((callback, receiver))
+    }
+
+    // See `Reactor::remove_callback`.
+    pub fn remove_callback(
+        &self,
+        cell: CellId,
+        callback: CallbackId,
+    ) -> Result<(), RemoveCallbackError> {
+        self.run(move |r| r.remove_callback(cell, callback))
+    }
+}
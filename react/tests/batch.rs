@@ -0,0 +1,82 @@
+use std::cell::RefCell;
+
+use react::*;
+
+#[test]
+fn batch_propagates_once() {
+    let calls = RefCell::new(vec![]);
+    let mut reactor = Reactor::new();
+    let a = reactor.create_input(1);
+    let b = reactor.create_input(2);
+    let c = reactor.create_input(3);
+    let sum = reactor
+        .create_compute(
+            &[CellId::Input(a), CellId::Input(b), CellId::Input(c)],
+            |v| v[0] + v[1] + v[2],
+        )
+        .unwrap();
+    reactor
+        .add_callback(CellId::Compute(sum), |_, v| calls.borrow_mut().push(*v))
+        .unwrap();
+    reactor.batch(|tx| {
+        assert!(tx.set(a, 10));
+        assert!(tx.set(b, 20));
+        assert!(tx.set(c, 30));
+    });
+    assert_eq!(*calls.borrow(), [60]);
+    assert_eq!(reactor.value(CellId::Compute(sum)), Some(60));
+}
+
+#[test]
+fn changes_that_cancel_out_do_not_call_callbacks() {
+    let calls = RefCell::new(vec![]);
+    let mut reactor = Reactor::new();
+    let a = reactor.create_input(1);
+    let b = reactor.create_input(2);
+    let sum = reactor
+        .create_compute(&[CellId::Input(a), CellId::Input(b)], |v| v[0] + v[1])
+        .unwrap();
+    reactor
+        .add_callback(CellId::Compute(sum), |_, v| calls.borrow_mut().push(*v))
+        .unwrap();
+    reactor.batch(|tx| {
+        tx.set(a, 2);
+        tx.set(b, 1);
+    });
+    reactor.batch(|tx| {
+        tx.set(a, 5);
+        tx.set(a, 2);
+    });
+    assert!(calls.borrow().is_empty());
+}
+
+#[test]
+fn compute_cells_update_when_the_batch_ends() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let double = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] * 2)
+        .unwrap();
+    let seen = reactor.batch(|tx| {
+        tx.set(input, 4);
+        (
+            tx.value(CellId::Input(input)),
+            tx.value(CellId::Compute(double)),
+        )
+    });
+    assert_eq!(seen, (Some(4), Some(2)));
+    assert_eq!(reactor.value(CellId::Compute(double)), Some(8));
+}
+
+#[test]
+fn setting_a_nonexistent_input_in_a_batch() {
+    let mut dummy_reactor = Reactor::new();
+    let input = dummy_reactor.create_input(1);
+    let mut reactor = Reactor::new();
+    let other = reactor.create_input(1);
+    reactor.batch(|tx| {
+        assert!(!tx.set(input, 0));
+        assert!(tx.set(other, 2));
+    });
+    assert_eq!(reactor.value(CellId::Input(other)), Some(2));
+}
@@ -0,0 +1,123 @@
+use std::cell::RefCell;
+
+use react::*;
+
+#[test]
+fn input_callbacks_see_old_and_new_values() {
+    let log = RefCell::new(vec![]);
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    reactor
+        .add_callback(CellId::Input(input), |&old, &new| {
+            log.borrow_mut().push((old, new))
+        })
+        .unwrap();
+    assert!(reactor.set_value(input, 2));
+    assert!(reactor.set_value(input, 2));
+    assert!(reactor.set_value(input, 5));
+    assert_eq!(*log.borrow(), [(1, 2), (2, 5)]);
+}
+
+#[test]
+fn compute_callbacks_see_old_and_new_values() {
+    let log = RefCell::new(vec![]);
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let double = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] * 2)
+        .unwrap();
+    reactor
+        .add_callback(CellId::Compute(double), |&old, &new| {
+            log.borrow_mut().push((old, new))
+        })
+        .unwrap();
+    assert!(reactor.set_value(input, 3));
+    assert!(reactor.set_value(input, 4));
+    assert_eq!(*log.borrow(), [(2, 6), (6, 8)]);
+}
+
+#[test]
+fn inputs_are_reported_before_their_dependents() {
+    let log = RefCell::new(vec![]);
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let plus_one = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    reactor
+        .add_callback(CellId::Compute(plus_one), |_, v| {
+            log.borrow_mut().push(format!("plus_one {v}"))
+        })
+        .unwrap();
+    reactor
+        .add_callback(CellId::Input(input), |_, v| {
+            log.borrow_mut().push(format!("input {v}"))
+        })
+        .unwrap();
+    assert!(reactor.set_value(input, 7));
+    assert_eq!(*log.borrow(), ["input 7", "plus_one 8"]);
+}
+
+#[test]
+fn batches_report_the_value_from_before_the_batch() {
+    let log = RefCell::new(vec![]);
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    reactor
+        .add_callback(CellId::Input(input), |&old, &new| {
+            log.borrow_mut().push((old, new))
+        })
+        .unwrap();
+    reactor.batch(|tx| {
+        tx.set(input, 2);
+        tx.set(input, 3);
+    });
+    reactor.batch(|tx| {
+        tx.set(input, 4);
+        tx.set(input, 3);
+    });
+    assert_eq!(*log.borrow(), [(1, 3)]);
+}
+
+#[test]
+fn input_callbacks_can_be_removed() {
+    let log = RefCell::new(vec![]);
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let callback = reactor
+        .add_callback(CellId::Input(input), |_, &new| log.borrow_mut().push(new))
+        .unwrap();
+    assert!(reactor.set_value(input, 2));
+    assert_eq!(
+        reactor.remove_callback(CellId::Input(input), callback),
+        Ok(())
+    );
+    assert_eq!(
+        reactor.remove_callback(CellId::Input(input), callback),
+        Err(RemoveCallbackError::NonexistentCallback)
+    );
+    assert!(reactor.set_value(input, 3));
+    assert_eq!(*log.borrow(), [2]);
+}
+
+#[test]
+fn callbacks_belong_to_their_cell() {
+    let mut reactor = Reactor::new();
+    let a = reactor.create_input(1);
+    let b = reactor.create_input(1);
+    let callback = reactor.add_callback(CellId::Input(a), |_, _| ()).unwrap();
+    assert_eq!(
+        reactor.remove_callback(CellId::Input(b), callback),
+        Err(RemoveCallbackError::NonexistentCallback)
+    );
+}
+
+#[test]
+fn adding_a_callback_to_a_nonexistent_input() {
+    let mut dummy_reactor = Reactor::new();
+    let input = dummy_reactor.create_input(1);
+    assert_eq!(
+        Reactor::new().add_callback(CellId::Input(input), |_: &i32, _: &i32| ()),
+        None
+    );
+}
@@ -0,0 +1,81 @@
+use react::*;
+
+#[test]
+fn ids_from_another_reactor_are_rejected() {
+    let mut one = Reactor::new();
+    let mut two = Reactor::new();
+    let a = one.create_input(1);
+    let b = two.create_input(2);
+    assert_ne!(a, b);
+    assert_eq!(two.value(CellId::Input(a)), None);
+    assert!(!two.set_value(a, 3));
+    assert_eq!(two.value(CellId::Input(b)), Some(2));
+    assert_eq!(
+        two.create_compute(&[CellId::Input(a)], |v| *v[0]),
+        Err(CellId::Input(a))
+    );
+    assert_eq!(
+        two.remove_cell(CellId::Input(a)),
+        Err(RemoveCellError::NonexistentCell)
+    );
+}
+
+#[test]
+fn callback_ids_from_another_reactor_are_rejected() {
+    let mut one = Reactor::new();
+    let mut two = Reactor::new();
+    let a = one.create_input(1);
+    let b = two.create_input(1);
+    let ca = one.create_compute(&[CellId::Input(a)], |v| *v[0]).unwrap();
+    let cb = two.create_compute(&[CellId::Input(b)], |v| *v[0]).unwrap();
+    let callback = one.add_callback(CellId::Compute(ca), |_, _| ()).unwrap();
+    let _ = two.add_callback(CellId::Compute(cb), |_, _| ()).unwrap();
+    assert_eq!(
+        two.remove_callback(CellId::Compute(ca), callback),
+        Err(RemoveCallbackError::NonexistentCell)
+    );
+    assert_eq!(
+        two.remove_callback(CellId::Compute(cb), callback),
+        Err(RemoveCallbackError::NonexistentCallback)
+    );
+}
+
+#[test]
+fn removed_ids_stay_invalid_when_their_slot_is_reused() {
+    let mut reactor = Reactor::new();
+    let old = reactor.create_input(1);
+    assert_eq!(reactor.remove_cell(CellId::Input(old)), Ok(()));
+    let new = reactor.create_input(2);
+    assert_ne!(old, new);
+    assert_eq!(reactor.value(CellId::Input(old)), None);
+    assert!(!reactor.set_value(old, 3));
+    assert_eq!(reactor.value(CellId::Input(new)), Some(2));
+}
+
+#[test]
+fn removed_callback_ids_stay_invalid() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let output = reactor
+        .create_compute(&[CellId::Input(input)], |v| *v[0])
+        .unwrap();
+    let old = reactor
+        .add_callback(CellId::Compute(output), |_, _| ())
+        .unwrap();
+    assert_eq!(
+        reactor.remove_callback(CellId::Compute(output), old),
+        Ok(())
+    );
+    let new = reactor
+        .add_callback(CellId::Compute(output), |_, _| ())
+        .unwrap();
+    assert_ne!(old, new);
+    assert_eq!(
+        reactor.remove_callback(CellId::Compute(output), old),
+        Err(RemoveCallbackError::NonexistentCallback)
+    );
+    assert_eq!(
+        reactor.remove_callback(CellId::Compute(output), new),
+        Ok(())
+    );
+}
@@ -0,0 +1,97 @@
+use std::cell::{Cell, RefCell};
+
+use react::*;
+
+#[test]
+fn diamond_cells_are_recomputed_once() {
+    let runs = Cell::new(0);
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let left = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    let right = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] * 2)
+        .unwrap();
+    let output = reactor
+        .create_compute(&[CellId::Compute(left), CellId::Compute(right)], |v| {
+            runs.set(runs.get() + 1);
+            v[0] + v[1]
+        })
+        .unwrap();
+    assert_eq!(runs.get(), 1);
+    assert!(reactor.set_value(input, 5));
+    assert_eq!(runs.get(), 2);
+    assert_eq!(reactor.value(CellId::Compute(output)), Some(16));
+}
+
+#[test]
+fn cells_only_see_settled_dependencies() {
+    let seen = RefCell::new(vec![]);
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    // a long and a short path to the same cell
+    let mut long = CellId::Input(input);
+    for _ in 0..4 {
+        long = CellId::Compute(reactor.create_compute(&[long], |v| *v[0]).unwrap());
+    }
+    let output = reactor
+        .create_compute(&[CellId::Input(input), long], |v| {
+            seen.borrow_mut().push((*v[0], *v[1]));
+            v[0] - v[1]
+        })
+        .unwrap();
+    assert!(reactor.set_value(input, 7));
+    assert!(reactor.set_value(input, 3));
+    assert_eq!(*seen.borrow(), [(1, 1), (7, 7), (3, 3)]);
+    assert_eq!(reactor.value(CellId::Compute(output)), Some(0));
+}
+
+#[test]
+fn unchanged_cells_stop_propagation() {
+    let runs = Cell::new(0);
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1i32);
+    let sign = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0].signum())
+        .unwrap();
+    let _ = reactor
+        .create_compute(&[CellId::Compute(sign)], |v| {
+            runs.set(runs.get() + 1);
+            *v[0]
+        })
+        .unwrap();
+    assert!(reactor.set_value(input, 9));
+    assert_eq!(runs.get(), 1);
+    assert!(reactor.set_value(input, -9));
+    assert_eq!(runs.get(), 2);
+}
+
+#[test]
+fn callbacks_run_after_the_graph_settles_in_dependency_order() {
+    let log = RefCell::new(vec![]);
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let plus_one = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    let sum = reactor
+        .create_compute(&[CellId::Input(input), CellId::Compute(plus_one)], |v| {
+            log.borrow_mut().push(format!("compute {}", v[0] + v[1]));
+            v[0] + v[1]
+        })
+        .unwrap();
+    reactor
+        .add_callback(CellId::Compute(sum), |_, v| {
+            log.borrow_mut().push(format!("sum {v}"))
+        })
+        .unwrap();
+    reactor
+        .add_callback(CellId::Compute(plus_one), |_, v| {
+            log.borrow_mut().push(format!("plus_one {v}"))
+        })
+        .unwrap();
+    log.borrow_mut().clear();
+    assert!(reactor.set_value(input, 2));
+    assert_eq!(*log.borrow(), ["compute 5", "plus_one 3", "sum 5"]);
+}
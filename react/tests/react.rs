@@ -170,7 +170,7 @@ fn compute_cells_fire_callbacks() {
         .create_compute(&[CellId::Input(input)], |v| v[0] + 1)
         .unwrap();
     assert!(reactor
-        .add_callback(output, |v| cb.callback_called(v))
+        .add_callback(CellId::Compute(output), |_, v| cb.callback_called(*v))
         .is_some());
     assert!(reactor.set_value(input, 3));
     cb.expect_to_have_been_called_with(4);
@@ -185,7 +185,7 @@ fn error_adding_callback_to_nonexistent_cell() {
         .create_compute(&[CellId::Input(input)], |_| 0)
         .unwrap();
     assert_eq!(
-        Reactor::new().add_callback(output, |_: u32| println!("hi")),
+        Reactor::new().add_callback(CellId::Compute(output), |_: &u32, _: &u32| println!("hi")),
         None
     );
 }
@@ -207,9 +207,11 @@ fn error_removing_callback_from_nonexisting_cell() {
     let output = reactor
         .create_compute(&[CellId::Input(input)], |_| 0)
         .unwrap();
-    let callback = reactor.add_callback(output, |_| ()).unwrap();
+    let callback = reactor
+        .add_callback(CellId::Compute(output), |_, _| ())
+        .unwrap();
     assert_eq!(
-        reactor.remove_callback(dummy_output, callback),
+        reactor.remove_callback(CellId::Compute(dummy_output), callback),
         Err(RemoveCallbackError::NonexistentCell)
     );
 }
@@ -223,11 +225,11 @@ fn callbacks_only_fire_on_change() {
     let output = reactor
         .create_compute(
             &[CellId::Input(input)],
-            |v| if v[0] < 3 { 111 } else { 222 },
+            |v| if *v[0] < 3 { 111 } else { 222 },
         )
         .unwrap();
     assert!(reactor
-        .add_callback(output, |v| cb.callback_called(v))
+        .add_callback(CellId::Compute(output), |_, v| cb.callback_called(*v))
         .is_some());
 
     assert!(reactor.set_value(input, 2));
@@ -246,7 +248,7 @@ fn callbacks_can_be_called_multiple_times() {
         .create_compute(&[CellId::Input(input)], |v| v[0] + 1)
         .unwrap();
     assert!(reactor
-        .add_callback(output, |v| cb.callback_called(v))
+        .add_callback(CellId::Compute(output), |_, v| cb.callback_called(*v))
         .is_some());
 
     assert!(reactor.set_value(input, 2));
@@ -269,10 +271,10 @@ fn callbacks_can_be_called_from_multiple_cells() {
         .create_compute(&[CellId::Input(input)], |v| v[0] - 1)
         .unwrap();
     assert!(reactor
-        .add_callback(plus_one, |v| cb1.callback_called(v))
+        .add_callback(CellId::Compute(plus_one), |_, v| cb1.callback_called(*v))
         .is_some());
     assert!(reactor
-        .add_callback(minus_one, |v| cb2.callback_called(v))
+        .add_callback(CellId::Compute(minus_one), |_, v| cb2.callback_called(*v))
         .is_some());
 
     assert!(reactor.set_value(input, 10));
@@ -294,19 +296,21 @@ fn callbacks_can_be_added_and_removed() {
         .unwrap();
 
     let callback = reactor
-        .add_callback(output, |v| cb1.callback_called(v))
+        .add_callback(CellId::Compute(output), |_, v| cb1.callback_called(*v))
         .unwrap();
     assert!(reactor
-        .add_callback(output, |v| cb2.callback_called(v))
+        .add_callback(CellId::Compute(output), |_, v| cb2.callback_called(*v))
         .is_some());
 
     assert!(reactor.set_value(input, 31));
     cb1.expect_to_have_been_called_with(32);
     cb2.expect_to_have_been_called_with(32);
 
-    assert!(reactor.remove_callback(output, callback).is_ok());
     assert!(reactor
-        .add_callback(output, |v| cb3.callback_called(v))
+        .remove_callback(CellId::Compute(output), callback)
+        .is_ok());
+    assert!(reactor
+        .add_callback(CellId::Compute(output), |_, v| cb3.callback_called(*v))
         .is_some());
 
     assert!(reactor.set_value(input, 41));
@@ -327,16 +331,18 @@ fn removing_a_callback_multiple_times_doesnt_interfere_with_other_callbacks() {
         .create_compute(&[CellId::Input(input)], |v| v[0] + 1)
         .unwrap();
     let callback = reactor
-        .add_callback(output, |v| cb1.callback_called(v))
+        .add_callback(CellId::Compute(output), |_, v| cb1.callback_called(*v))
         .unwrap();
     assert!(reactor
-        .add_callback(output, |v| cb2.callback_called(v))
+        .add_callback(CellId::Compute(output), |_, v| cb2.callback_called(*v))
         .is_some());
     // We want the first remove to be Ok, but the others should be errors.
-    assert!(reactor.remove_callback(output, callback).is_ok());
+    assert!(reactor
+        .remove_callback(CellId::Compute(output), callback)
+        .is_ok());
     for _ in 1..5 {
         assert_eq!(
-            reactor.remove_callback(output, callback),
+            reactor.remove_callback(CellId::Compute(output), callback),
             Err(RemoveCallbackError::NonexistentCallback)
         );
     }
@@ -368,7 +374,7 @@ fn callbacks_should_only_be_called_once_even_if_multiple_dependencies_change() {
         )
         .unwrap();
     assert!(reactor
-        .add_callback(output, |v| cb.callback_called(v))
+        .add_callback(CellId::Compute(output), |_, v| cb.callback_called(*v))
         .is_some());
     assert!(reactor.set_value(input, 4));
     cb.expect_to_have_been_called_with(10);
@@ -393,7 +399,7 @@ fn callbacks_should_not_be_called_if_dependencies_change_but_output_value_doesnt
         )
         .unwrap();
     assert!(reactor
-        .add_callback(always_two, |v| cb.callback_called(v))
+        .add_callback(CellId::Compute(always_two), |_, v| cb.callback_called(*v))
         .is_some());
     for i in 2..5 {
         assert!(reactor.set_value(input, i));
@@ -422,16 +428,16 @@ fn adder_with_boolean_values() {
 
     let a_xor_b_and_cin = reactor
         .create_compute(&[CellId::Compute(a_xor_b), CellId::Input(carry_in)], |v| {
-            v[0] && v[1]
+            *v[0] && *v[1]
         })
         .unwrap();
     let a_and_b = reactor
-        .create_compute(&[CellId::Input(a), CellId::Input(b)], |v| v[0] && v[1])
+        .create_compute(&[CellId::Input(a), CellId::Input(b)], |v| *v[0] && *v[1])
         .unwrap();
     let carry_out = reactor
         .create_compute(
             &[CellId::Compute(a_xor_b_and_cin), CellId::Compute(a_and_b)],
-            |v| v[0] || v[1],
+            |v| *v[0] || *v[1],
         )
         .unwrap();
 
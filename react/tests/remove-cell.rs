@@ -0,0 +1,144 @@
+use std::cell::Cell;
+
+use react::*;
+
+#[test]
+fn unused_cells_can_be_removed() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let output = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    assert_eq!(reactor.remove_cell(CellId::Compute(output)), Ok(()));
+    assert_eq!(reactor.value(CellId::Compute(output)), None);
+    assert_eq!(reactor.remove_cell(CellId::Input(input)), Ok(()));
+    assert_eq!(reactor.value(CellId::Input(input)), None);
+    assert!(!reactor.set_value(input, 2));
+}
+
+#[test]
+fn removing_a_nonexistent_cell() {
+    let mut dummy_reactor = Reactor::new();
+    let input = dummy_reactor.create_input(1);
+    let mut reactor = Reactor::<i32>::new();
+    assert_eq!(
+        reactor.remove_cell(CellId::Input(input)),
+        Err(RemoveCellError::NonexistentCell)
+    );
+    assert_eq!(
+        reactor.remove_cell_cascade(CellId::Input(input)),
+        Err(RemoveCellError::NonexistentCell)
+    );
+}
+
+#[test]
+fn cells_with_dependents_are_kept() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let plus_one = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    let times_two = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] * 2)
+        .unwrap();
+    assert_eq!(
+        reactor.remove_cell(CellId::Input(input)),
+        Err(RemoveCellError::HasDependents(vec![plus_one, times_two]))
+    );
+    assert!(reactor.set_value(input, 3));
+    assert_eq!(reactor.value(CellId::Compute(plus_one)), Some(4));
+    assert_eq!(reactor.value(CellId::Compute(times_two)), Some(6));
+}
+
+#[test]
+fn removed_dependents_no_longer_block_removal() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let output = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    assert!(reactor.remove_cell(CellId::Input(input)).is_err());
+    assert_eq!(reactor.remove_cell(CellId::Compute(output)), Ok(()));
+    assert_eq!(reactor.remove_cell(CellId::Input(input)), Ok(()));
+}
+
+#[test]
+fn removed_cells_stop_propagating() {
+    let calls = Cell::new(0);
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let kept = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    let removed = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] * 2)
+        .unwrap();
+    reactor
+        .add_callback(CellId::Compute(removed), |_, _| calls.set(calls.get() + 1))
+        .unwrap();
+    assert_eq!(reactor.remove_cell(CellId::Compute(removed)), Ok(()));
+    assert!(reactor.set_value(input, 5));
+    assert_eq!(reactor.value(CellId::Compute(kept)), Some(6));
+    assert_eq!(calls.get(), 0);
+}
+
+#[test]
+fn cascade_removes_every_dependent_and_its_callbacks() {
+    let calls = Cell::new(0);
+    let mut reactor = Reactor::new();
+    let a = reactor.create_input(1);
+    let b = reactor.create_input(2);
+    let sum = reactor
+        .create_compute(&[CellId::Input(a), CellId::Input(b)], |v| v[0] + v[1])
+        .unwrap();
+    let double = reactor
+        .create_compute(&[CellId::Compute(sum)], |v| v[0] * 2)
+        .unwrap();
+    // depends on `a` both directly and through `sum`
+    let diamond = reactor
+        .create_compute(&[CellId::Input(a), CellId::Compute(double)], |v| {
+            v[0] + v[1]
+        })
+        .unwrap();
+    let other = reactor
+        .create_compute(&[CellId::Input(b)], |v| v[0] * 10)
+        .unwrap();
+    for cell in [sum, double, diamond] {
+        reactor
+            .add_callback(CellId::Compute(cell), |_, _| calls.set(calls.get() + 1))
+            .unwrap();
+    }
+
+    assert_eq!(reactor.remove_cell_cascade(CellId::Input(a)), Ok(()));
+    for cell in [sum, double, diamond] {
+        assert_eq!(reactor.value(CellId::Compute(cell)), None);
+    }
+    assert_eq!(reactor.value(CellId::Input(a)), None);
+
+    assert!(reactor.set_value(b, 3));
+    assert_eq!(reactor.value(CellId::Compute(other)), Some(30));
+    assert_eq!(calls.get(), 0);
+    assert_eq!(
+        reactor.remove_cell(CellId::Input(b)),
+        Err(RemoveCellError::HasDependents(vec![other]))
+    );
+}
+
+#[test]
+fn cascade_on_a_compute_cell_keeps_its_dependencies() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let plus_one = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    let plus_two = reactor
+        .create_compute(&[CellId::Compute(plus_one)], |v| v[0] + 1)
+        .unwrap();
+    assert_eq!(
+        reactor.remove_cell_cascade(CellId::Compute(plus_one)),
+        Ok(())
+    );
+    assert_eq!(reactor.value(CellId::Compute(plus_two)), None);
+    assert_eq!(reactor.value(CellId::Input(input)), Some(1));
+    assert_eq!(reactor.remove_cell(CellId::Input(input)), Ok(()));
+}
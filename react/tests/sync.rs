@@ -0,0 +1,167 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    mpsc, Arc,
+};
+use std::thread;
+
+use react::*;
+
+#[test]
+fn is_send_and_sync() {
+    fn shareable<T: Send + Sync>() {}
+    shareable::<SyncReactor<i32>>();
+    shareable::<SyncReactor<String>>();
+}
+
+#[test]
+fn behaves_like_a_reactor() {
+    let reactor = SyncReactor::new();
+    let input = reactor.create_input(1);
+    let output = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    assert_eq!(reactor.value(CellId::Compute(output)), Some(2));
+    assert!(reactor.set_value(input, 3));
+    assert_eq!(reactor.value(CellId::Compute(output)), Some(4));
+    assert_eq!(
+        reactor.remove_cell(CellId::Input(input)),
+        Err(RemoveCellError::HasDependents(vec![output]))
+    );
+    assert_eq!(reactor.remove_cell_cascade(CellId::Input(input)), Ok(()));
+    assert_eq!(reactor.value(CellId::Compute(output)), None);
+}
+
+#[test]
+fn inputs_can_be_set_from_many_threads() {
+    let reactor = SyncReactor::new();
+    let inputs: Vec<_> = (0..8).map(|_| reactor.create_input(0)).collect();
+    let deps: Vec<_> = inputs.iter().map(|&id| CellId::Input(id)).collect();
+    let sum = reactor
+        .create_compute(&deps, |v| v.iter().copied().sum::<i32>())
+        .unwrap();
+    let handles: Vec<_> = inputs
+        .iter()
+        .map(|&input| {
+            let reactor = reactor.clone();
+            thread::spawn(move || {
+                for value in 1..=100 {
+                    assert!(reactor.set_value(input, value));
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(reactor.value(CellId::Compute(sum)), Some(800));
+}
+
+#[test]
+fn propagation_is_serialized() {
+    let reactor = SyncReactor::new();
+    let a = reactor.create_input(0);
+    let b = reactor.create_input(0);
+    let diff = reactor
+        .create_compute(&[CellId::Input(a), CellId::Input(b)], |v| v[0] - v[1])
+        .unwrap();
+    let (_, changes) = reactor.subscribe(CellId::Compute(diff)).unwrap();
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let reactor = reactor.clone();
+            thread::spawn(move || {
+                for value in 1..=50 {
+                    reactor.batch(move |tx| {
+                        tx.set(a, value);
+                        tx.set(b, value);
+                    });
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    // a and b always change together, so their difference never does
+    assert!(changes.try_recv().is_err());
+    assert_eq!(reactor.value(CellId::Compute(diff)), Some(0));
+}
+
+#[test]
+fn subscribers_receive_old_and_new_values() {
+    let reactor = SyncReactor::new();
+    let input = reactor.create_input("a".to_string());
+    let upper = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0].to_uppercase())
+        .unwrap();
+    let (callback, changes) = reactor.subscribe(CellId::Compute(upper)).unwrap();
+    let feed = {
+        let reactor = reactor.clone();
+        thread::spawn(move || {
+            for s in ["b", "b", "c"] {
+                reactor.set_value(input, s.to_string());
+            }
+        })
+    };
+    feed.join().unwrap();
+    let received: Vec<_> = changes.try_iter().collect();
+    assert_eq!(
+        received,
+        [
+            ("A".to_string(), "B".to_string()),
+            ("B".to_string(), "C".to_string())
+        ]
+    );
+    assert_eq!(
+        reactor.remove_callback(CellId::Compute(upper), callback),
+        Ok(())
+    );
+    reactor.set_value(input, "d".to_string());
+    assert!(changes.try_recv().is_err());
+}
+
+#[test]
+fn callbacks_run_on_the_reactor_thread() {
+    let reactor = SyncReactor::new();
+    let input = reactor.create_input(0);
+    let calls = Arc::new(AtomicUsize::new(0));
+    let (names, received) = mpsc::channel();
+    let counter = Arc::clone(&calls);
+    reactor
+        .add_callback(CellId::Input(input), move |_, _| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            let _ = names.send(thread::current().name().map(str::to_string));
+        })
+        .unwrap();
+    assert!(reactor.set_value(input, 1));
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+    assert_eq!(received.recv().unwrap().as_deref(), Some("reactor"));
+}
+
+#[test]
+fn subscribing_to_a_nonexistent_cell() {
+    let mut dummy_reactor = Reactor::new();
+    let input = dummy_reactor.create_input(1);
+    assert!(SyncReactor::<i32>::new()
+        .subscribe(CellId::Input(input))
+        .is_none());
+}
+
+#[test]
+fn panics_reach_only_the_caller_that_caused_them() {
+    let reactor = SyncReactor::new();
+    let input = reactor.create_input(1);
+    let inverse = reactor
+        .create_compute(&[CellId::Input(input)], |v| 100 / v[0])
+        .unwrap();
+    let failing = {
+        let reactor = reactor.clone();
+        thread::spawn(move || reactor.set_value(input, 0))
+    };
+    assert!(failing.join().is_err());
+    // the reactor keeps serving every clone
+    assert_eq!(reactor.value(CellId::Input(input)), Some(0));
+    assert!(reactor.set_value(input, 4));
+    assert_eq!(reactor.value(CellId::Compute(inverse)), Some(25));
+    let other = reactor.clone();
+    assert_eq!(other.value(CellId::Compute(inverse)), Some(25));
+}
@@ -0,0 +1,78 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use react::*;
+
+#[test]
+fn string_cells() {
+    let seen = RefCell::new(vec![]);
+    let mut reactor = Reactor::new();
+    let first = reactor.create_input("Ada".to_string());
+    let last = reactor.create_input("Lovelace".to_string());
+    let full = reactor
+        .create_compute(&[CellId::Input(first), CellId::Input(last)], |v| {
+            format!("{} {}", v[0], v[1])
+        })
+        .unwrap();
+    reactor
+        .add_callback(CellId::Compute(full), |_, v| {
+            seen.borrow_mut().push(v.clone())
+        })
+        .unwrap();
+    assert_eq!(
+        reactor.value(CellId::Compute(full)).as_deref(),
+        Some("Ada Lovelace")
+    );
+    assert!(reactor.set_value(first, "Augusta".to_string()));
+    assert_eq!(*seen.borrow(), ["Augusta Lovelace"]);
+}
+
+#[test]
+fn vector_cells() {
+    let mut reactor = Reactor::new();
+    let items = reactor.create_input(vec![3, 1, 2]);
+    let sorted = reactor
+        .create_compute(&[CellId::Input(items)], |v| {
+            let mut sorted = v[0].clone();
+            sorted.sort();
+            sorted
+        })
+        .unwrap();
+    let total = reactor
+        .create_compute(&[CellId::Compute(sorted)], |v| vec![v[0].iter().sum()])
+        .unwrap();
+    assert!(reactor.set_value(items, vec![5, 4]));
+    assert_eq!(
+        reactor.value_ref(CellId::Compute(sorted)),
+        Some(&vec![4, 5])
+    );
+    assert_eq!(reactor.value(CellId::Compute(total)), Some(vec![9]));
+}
+
+#[test]
+fn compute_functions_borrow_their_dependencies() {
+    let mut reactor = Reactor::new();
+    let shared = Rc::new("shared".to_string());
+    let input = reactor.create_input(Rc::clone(&shared));
+    let upper = reactor
+        .create_compute(&[CellId::Input(input)], |v| Rc::new(v[0].to_uppercase()))
+        .unwrap();
+    // only the reactor and this test hold the input value
+    assert_eq!(Rc::strong_count(&shared), 2);
+    assert_eq!(
+        reactor
+            .value_ref(CellId::Compute(upper))
+            .map(|s| s.as_str()),
+        Some("SHARED")
+    );
+}
+
+#[test]
+fn value_ref_of_a_nonexistent_cell() {
+    let mut dummy_reactor = Reactor::new();
+    let input = dummy_reactor.create_input(String::new());
+    assert_eq!(
+        Reactor::<String>::new().value_ref(CellId::Input(input)),
+        None
+    );
+}